@@ -0,0 +1,37 @@
+//!
+//! A simple benchmark of merging translations, comparing `Y10n::localize`, which merges the
+//! languages on every call, with `Y10n::localize_cached`, which reuses the merged result
+//!
+//! Run with `cargo bench --bench localize`
+//!
+
+use std::time::Instant;
+use y10n::*;
+
+const ITERATIONS: u64 = 20_000;
+
+fn main() {
+    let y10n = Y10n::from_glob("fixtures/partial/*.yml").expect("Failed to load translations");
+    let languages = parse_accept_language("de, en-GB;q=0.8");
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(y10n.localize(&languages));
+    }
+    report("localize", start);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(y10n.localize_cached(&languages));
+    }
+    report("localize_cached", start);
+}
+
+fn report(name: &str, start: Instant) {
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>8} ns/iter",
+        name,
+        elapsed.as_nanos() / ITERATIONS as u128
+    );
+}
@@ -0,0 +1,55 @@
+//!
+//! A simple benchmark of the cost of looking up plural strings, comparing `Y10n::pluralize`, which
+//! looks up the token on every call, with `plural::select` on branches which have already been
+//! looked up
+//!
+//! Both are given the same language and counts, with the `Accept-Language` header parsed once up
+//! front, so the difference is the cost of the lookup rather than of header parsing. Plural rules
+//! themselves are plain functions chosen by a static `match` in `plural::rule_for`, so there is
+//! nothing to cache and they aren't measured separately.
+//!
+//! Run with `cargo bench --bench plural_lookup`
+//!
+
+use std::time::Instant;
+use y10n::*;
+
+const ITERATIONS: u64 = 200_000;
+
+fn main() {
+    let y10n = Y10n::from_sources(
+        &[(
+            "ru-RU",
+            b"items:\n  one: 'one'\n  few: 'few'\n  many: 'many'\n  other: 'other'",
+        )],
+        Format::Yaml,
+    )
+    .expect("Failed to load translations");
+    let languages = parse_accept_language("ru-RU");
+    let branches = y10n.localize(&languages)["items"]
+        .as_mapping()
+        .expect("Expected plural branches")
+        .clone();
+
+    let start = Instant::now();
+    for count in 0..ITERATIONS {
+        std::hint::black_box(y10n.pluralize("items", count, &languages));
+    }
+    report("pluralize", start);
+
+    let code = &languages[0].code;
+    let start = Instant::now();
+    for count in 0..ITERATIONS {
+        std::hint::black_box(plural::select(&branches, code, count));
+    }
+    report("select from looked up branches", start);
+}
+
+fn report(name: &str, start: Instant) {
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>8} ns/iter",
+        name,
+        elapsed.as_nanos() / ITERATIONS as u128
+    );
+}
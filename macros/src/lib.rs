@@ -0,0 +1,115 @@
+//!
+//! The y10n-macros crate contains the procedural macros which are re-exported by `y10n` when its
+//! `macros` feature is enabled
+//!
+
+#![warn(missing_docs)]
+use proc_macro::TokenStream;
+use quote::quote;
+use std::path::PathBuf;
+use syn::{parse_macro_input, LitStr};
+
+/// The reference translation file used when `Y10N_REFERENCE` is not set
+const DEFAULT_REFERENCE: &str = "l10n/en.yml";
+
+/**
+ * Validate at compile time that a localization key exists in the reference translation file
+ *
+ * The reference file defaults to `l10n/en.yml` relative to the crate being compiled, and can be
+ * changed by setting the `Y10N_REFERENCE` environment variable at build time. The macro expands to
+ * the key itself, so it can be used anywhere a `&'static str` key is expected:
+ *
+ * ```ignore
+ * let greeting = y10n.lookup(t_key!("greeting"), &languages);
+ * ```
+ *
+ * A key which is missing from the reference file fails the build.
+ */
+#[proc_macro]
+pub fn t_key(input: TokenStream) -> TokenStream {
+    let key = parse_macro_input!(input as LitStr);
+
+    match validate(&key.value()) {
+        Ok(path) => {
+            let path = path.to_string_lossy().to_string();
+            quote!({
+                // Ensure the crate is rebuilt whenever the reference file changes
+                const _: &[u8] = include_bytes!(#path);
+                #key
+            })
+            .into()
+        }
+        Err(message) => syn::Error::new(key.span(), message)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/**
+ * Locate the reference translation file for the crate currently being compiled
+ */
+fn reference_path() -> PathBuf {
+    let reference =
+        std::env::var("Y10N_REFERENCE").unwrap_or_else(|_| DEFAULT_REFERENCE.to_string());
+    let path = PathBuf::from(reference);
+
+    if path.is_absolute() {
+        return path;
+    }
+
+    match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => PathBuf::from(dir).join(path),
+        Err(_) => path,
+    }
+}
+
+/**
+ * Check that the key exists in the reference file, returning the file's path on success
+ */
+fn validate(key: &str) -> Result<PathBuf, String> {
+    let path = reference_path();
+    let file = std::fs::File::open(&path).map_err(|e| {
+        format!(
+            "Failed to open the reference translations {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let value: serde_yaml::Value = serde_yaml::from_reader(file).map_err(|e| {
+        format!(
+            "Failed to deserialize the reference translations {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+
+    let found = value
+        .as_mapping()
+        .is_some_and(|map| contains_path(map, key));
+
+    if found {
+        Ok(path)
+    } else {
+        Err(format!(
+            "The localization key `{}` does not exist in {}",
+            key,
+            path.display()
+        ))
+    }
+}
+
+/**
+ * Check whether the dotted key resolves within the mapping the same way `Y10n::lookup` resolves
+ * it, preferring a literal key such as `"menu.file"` over the nested path `menu` -> `file`
+ */
+fn contains_path(map: &serde_yaml::Mapping, key: &str) -> bool {
+    if map.contains_key(&serde_yaml::Value::String(key.to_string())) {
+        return true;
+    }
+
+    key.match_indices('.').any(|(i, _)| {
+        map.get(&serde_yaml::Value::String(key[..i].to_string()))
+            .and_then(|value| value.as_mapping())
+            .is_some_and(|nested| contains_path(nested, &key[i + 1..]))
+    })
+}
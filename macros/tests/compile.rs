@@ -0,0 +1,13 @@
+#[test]
+fn t_key() {
+    std::env::set_var(
+        "Y10N_REFERENCE",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ui/en.yml"),
+    );
+
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/existing_key.rs");
+    t.pass("tests/ui/nested_key.rs");
+    t.compile_fail("tests/ui/missing_key.rs");
+    t.compile_fail("tests/ui/missing_nested_key.rs");
+}
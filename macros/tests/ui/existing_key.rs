@@ -0,0 +1,5 @@
+use y10n_macros::t_key;
+
+fn main() {
+    assert_eq!(t_key!("greeting"), "greeting");
+}
@@ -0,0 +1,5 @@
+use y10n_macros::t_key;
+
+fn main() {
+    let _ = t_key!("farewell");
+}
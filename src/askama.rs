@@ -0,0 +1,92 @@
+/// The askama module has filters for rendering localization strings in Askama
+/// templates, which can be enabled with the `askama` feature
+///
+/// Askama resolves filters through a `filters` module in scope of the template
+/// struct, so the filters need to be re-exported there:
+///
+/// ```rust
+/// use askama::Template;
+/// use y10n::{Language, Y10n};
+///
+/// mod filters {
+///     pub use y10n::askama::*;
+/// }
+///
+/// #[derive(Template)]
+/// #[template(source = r#"{{ "greeting"|t(y10n, languages) }}"#, ext = "txt")]
+/// struct Greeting<'a> {
+///     y10n: &'a Y10n,
+///     languages: Vec<Language>,
+/// }
+///
+/// let y10n = Y10n::from_glob("l10n/*.yml").unwrap();
+/// let greeting = Greeting { y10n: &y10n, languages: vec!["de".into()] };
+/// assert_eq!(greeting.render().unwrap(), "moin moin");
+/// ```
+pub use crate::{Language, Y10n};
+
+///
+/// Translate the key with the preferred languages, see `Y10n::translate`, which is
+/// used as `{{ "greeting"|t(y10n, languages) }}`
+///
+/// Keys which none of the languages have are rendered as an empty string, just
+/// like with the handlebars helper.
+pub fn t<K: AsRef<str>>(key: K, y10n: &Y10n, languages: &[Language]) -> ::askama::Result<String> {
+    Ok(y10n
+        .translate(key.as_ref(), languages)
+        .map(|translation| translation.into_owned())
+        .unwrap_or_default())
+}
+
+///
+/// Translate the key and interpolate its placeholders from the JSON context, see
+/// `Y10n::render_with`, which is used as
+/// `{{ "thankyou"|t_with(y10n, languages, context) }}`
+pub fn t_with<K: AsRef<str>>(
+    key: K,
+    y10n: &Y10n,
+    languages: &[Language],
+    context: &serde_json::Value,
+) -> ::askama::Result<String> {
+    Ok(y10n
+        .render_with(key.as_ref(), languages, context)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::askama::Template;
+
+    mod filters {
+        pub use crate::askama::*;
+    }
+
+    #[derive(Template)]
+    #[template(path = "greeting.html")]
+    struct Greeting<'a> {
+        y10n: &'a Y10n,
+        languages: Vec<Language>,
+        context: serde_json::Value,
+    }
+
+    #[test]
+    fn test_render_template() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        let greeting = Greeting {
+            y10n: &y10n,
+            languages: vec!["en".into()],
+            context: serde_json::json!({"team": "Foo & Bar"}),
+        };
+        assert_eq!(
+            greeting.render().expect("Failed to render"),
+            "<h1>hello world</h1>\n<p>Thanks for playing Foo &amp; Bar!</p>"
+        );
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        assert_eq!(t("missing", &y10n, &["en".into()]).unwrap(), "");
+    }
+}
@@ -0,0 +1,117 @@
+/// The axum module has an extractor for the preferred languages of a request,
+/// which can be enabled with the `axum` feature
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header::ACCEPT_LANGUAGE;
+use axum::http::request::Parts;
+use log::*;
+use std::convert::Infallible;
+use std::ops::Deref;
+use std::sync::Arc;
+
+pub use crate::{Language, Y10n};
+
+///  This extractor negotiates the preferred languages of a request from its
+///  `Accept-Language` header, ready to be passed to `Y10n::localize` or
+///  `Y10n::lookup`
+///
+///  The header is negotiated with `Y10n::negotiate_languages` against the `Y10n` in the
+///  application state, so only languages which have actually been loaded are
+///  extracted, sorted by descending quality. When the header is absent, empty,
+///  or matches none of the loaded languages, the default language of the `Y10n`
+///  (see `Y10n::set_default_language`) is extracted instead.
+///
+///  The state needs to provide an `Arc<Y10n>`, either directly or through
+///  `FromRef`:
+///
+///  ```rust
+///  use axum::{routing::get, Router};
+///  use std::sync::Arc;
+///  use y10n::axum::*;
+///
+///  async fn greet(
+///      axum::extract::State(y10n): axum::extract::State<Arc<Y10n>>,
+///      languages: PreferredLanguages,
+///  ) -> String {
+///      y10n.lookup("greeting", &languages).unwrap_or_default().to_string()
+///  }
+///
+///  let y10n = Arc::new(Y10n::from_glob("l10n/*.yml").unwrap());
+///  let app: Router = Router::new().route("/", get(greet)).with_state(y10n);
+///  ```
+#[derive(Clone, Debug, Default)]
+pub struct PreferredLanguages(pub Vec<Language>);
+
+impl Deref for PreferredLanguages {
+    type Target = [Language];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<PreferredLanguages> for Vec<Language> {
+    fn from(languages: PreferredLanguages) -> Self {
+        languages.0
+    }
+}
+
+impl<S> FromRequestParts<S> for PreferredLanguages
+where
+    Arc<Y10n>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let y10n = Arc::<Y10n>::from_ref(state);
+        let header = parts
+            .headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        trace!("Negotiating languages for: {:?}", header);
+
+        let requested = crate::parse_accept_language(header);
+        Ok(PreferredLanguages(y10n.negotiate_languages(&requested)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn y10n() -> Arc<Y10n> {
+        let mut y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        y10n.set_default_language("en");
+        Arc::new(y10n)
+    }
+
+    async fn extract(header: Option<&str>) -> Vec<String> {
+        let mut request = Request::builder();
+        if let Some(header) = header {
+            request = request.header(ACCEPT_LANGUAGE, header);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+
+        let languages = PreferredLanguages::from_request_parts(&mut parts, &y10n())
+            .await
+            .unwrap();
+        languages.iter().map(|lang| lang.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_extract_languages() {
+        assert_eq!(
+            extract(Some("fr, en;q=0.5, de-DE;q=0.8")).await,
+            vec!["de;q=0.8", "en;q=0.5"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_default() {
+        assert_eq!(extract(None).await, vec!["en"]);
+        assert_eq!(extract(Some("")).await, vec!["en"]);
+        assert_eq!(extract(Some("fr")).await, vec!["en"]);
+    }
+}
@@ -0,0 +1,120 @@
+//!
+//! Validate the translation files matched by a glob against a base language, reporting files
+//! which fail to load, missing keys and placeholder mismatches
+//!
+//! ```text
+//! y10n-lint [--format text|json] <glob> <base>
+//! ```
+//!
+//! Exits with 1 when any problems were found, and 2 when the arguments are invalid.
+//!
+
+use std::convert::TryFrom;
+use std::process::exit;
+use y10n::*;
+
+const USAGE: &str = "Usage: y10n-lint [--format text|json] <glob> <base>";
+
+#[derive(PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+fn main() {
+    let (pattern, base, format) = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            exit(2);
+        }
+    };
+
+    let (y10n, failures) = Y10n::from_glob_lenient(&pattern);
+    let mut missing: Vec<(String, Vec<String>)> = y10n.missing_keys(&base).into_iter().collect();
+    missing.sort();
+    let mismatches = y10n.validate_placeholders(&base);
+    let loaded = y10n.languages().iter().any(|language| **language == base);
+
+    if format == OutputFormat::Json {
+        let report = serde_json::json!({
+            "base": base,
+            "loaded": loaded,
+            "failures": failures
+                .iter()
+                .map(|(path, error)| serde_json::json!({
+                    "path": path.display().to_string(),
+                    "error": error.to_string(),
+                }))
+                .collect::<Vec<_>>(),
+            "missing_keys": missing
+                .iter()
+                .map(|(language, keys)| serde_json::json!({"language": language, "keys": keys}))
+                .collect::<Vec<_>>(),
+            "placeholder_mismatches": mismatches
+                .iter()
+                .map(|mismatch| serde_json::json!({
+                    "language": mismatch.language,
+                    "key": mismatch.key,
+                    "missing": mismatch.missing,
+                    "extra": mismatch.extra,
+                }))
+                .collect::<Vec<_>>(),
+        });
+        println!("{}", report);
+    } else {
+        if !loaded {
+            println!("{}", ValidationError::MissingReference(base.clone()));
+        }
+        for (path, error) in failures.iter() {
+            println!("{}: {}", path.display(), error);
+        }
+        for (language, keys) in missing.iter() {
+            let missing = ValidationError::MissingKeys {
+                language: language.clone(),
+                keys: keys.clone(),
+            };
+            println!("{}", missing);
+        }
+        for mismatch in mismatches.iter() {
+            println!("{}", ValidationError::PlaceholderMismatch(mismatch.clone()));
+        }
+    }
+
+    if !loaded || !failures.is_empty() || !missing.is_empty() || !mismatches.is_empty() {
+        exit(1);
+    }
+}
+
+/**
+ * Parse the glob, base language and output format from the command line arguments
+ */
+fn parse_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<(String, String, OutputFormat), String> {
+    let mut format = OutputFormat::Text;
+    let mut positional = vec![];
+
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--format" => args.next(),
+            _ => match arg.strip_prefix("--format=") {
+                Some(value) => Some(value.to_string()),
+                None => {
+                    positional.push(arg);
+                    continue;
+                }
+            },
+        };
+        format = match value.as_deref() {
+            Some("text") => OutputFormat::Text,
+            Some("json") => OutputFormat::Json,
+            other => return Err(format!("Unknown output format: {:?}", other.unwrap_or(""))),
+        };
+    }
+
+    match <[String; 2]>::try_from(positional) {
+        Ok([pattern, base]) => Ok((pattern, base, format)),
+        Err(_) => Err("Expected a glob and a base language".to_string()),
+    }
+}
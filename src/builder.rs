@@ -0,0 +1,280 @@
+//!
+//! The builder for configuring a Y10n instance once, before its translations are loaded
+//!
+
+use crate::{
+    Error, KeyFn, Layout, LoadOptions, MergeStrategy, Whitespace, Y10n, DEFAULT_MAX_DEPTH,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/**
+ * Y10nBuilder configures the settings of a `Y10n` and the globs its translations are loaded from
+ *
+ * The settings are applied before any translations are loaded, so that settings such as the
+ * `MergeStrategy` also apply while merging the files of the globs.
+ *
+ * ```rust
+ * use y10n::*;
+ * let y10n = Y10n::builder()
+ *     .glob("l10n/**/*.yml")
+ *     .default_language("en")
+ *     .merge_strategy(MergeStrategy::ConcatSequences)
+ *     .build()
+ *     .expect("Failed to load translations");
+ * assert_eq!(Some("hello world"), y10n.lookup("greeting", &parse_accept_language("*")));
+ * ```
+ */
+#[derive(Clone, Debug)]
+pub struct Y10nBuilder {
+    globs: Vec<String>,
+    layout: Layout,
+    key_fn: Option<KeyDeriver>,
+    options: LoadOptions,
+    default_language: Option<String>,
+    fallback_only: Vec<String>,
+    merge_strategy: MergeStrategy,
+    max_depth: usize,
+    strict_regions: bool,
+    whitespace: Whitespace,
+}
+
+/**
+ * The closure which derives the language key of a translation file from its path
+ */
+#[derive(Clone)]
+struct KeyDeriver(Arc<KeyFn>);
+
+impl std::fmt::Debug for KeyDeriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KeyDeriver")
+    }
+}
+
+impl Default for Y10nBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Y10nBuilder {
+    /**
+     * Create a builder with the default settings and no globs, which builds an empty `Y10n`
+     */
+    pub fn new() -> Self {
+        Self {
+            globs: vec![],
+            layout: Layout::default(),
+            key_fn: None,
+            options: LoadOptions::default(),
+            default_language: None,
+            fallback_only: vec![],
+            merge_strategy: MergeStrategy::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            strict_regions: false,
+            whitespace: Whitespace::default(),
+        }
+    }
+
+    /**
+     * Add a glob of translation files to load, see `Y10n::from_globs` for how multiple globs are
+     * merged
+     */
+    pub fn glob(mut self, pattern: &str) -> Self {
+        self.globs.push(pattern.to_string());
+        self
+    }
+
+    /**
+     * Set how the translation files of the globs are laid out, see `Y10n::from_glob_with_layout`
+     */
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /**
+     * Derive the language key of each file from its path with the closure, see
+     * `Y10n::from_glob_with_key`
+     */
+    pub fn key_with<F>(mut self, key: F) -> Self
+    where
+        F: Fn(&Path) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key_fn = Some(KeyDeriver(Arc::new(key)));
+        self
+    }
+
+    /**
+     * Set the `LoadOptions` applied to each file as it is loaded
+     */
+    pub fn load_options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /**
+     * Set the default language, see `Y10n::set_default_language`
+     */
+    pub fn default_language(mut self, key: &str) -> Self {
+        self.default_language = Some(key.to_string());
+        self
+    }
+
+    /**
+     * Mark a language as only being used as a fallback, see `Y10n::set_fallback_only`
+     */
+    pub fn fallback_only(mut self, key: &str) -> Self {
+        self.fallback_only.push(key.to_string());
+        self
+    }
+
+    /**
+     * Set how sequences are merged, see `Y10n::set_merge_strategy`
+     */
+    pub fn merge_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.merge_strategy = strategy;
+        self
+    }
+
+    /**
+     * Set the maximum nesting of mappings which is merged, see `Y10n::set_max_depth`
+     */
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /**
+     * Set whether requested regions must be loaded, see `Y10n::set_strict_regions`
+     */
+    pub fn strict_regions(mut self, enabled: bool) -> Self {
+        self.strict_regions = enabled;
+        self
+    }
+
+    /**
+     * Set the treatment of whitespace in resolved strings, see `Y10n::set_whitespace`
+     */
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /**
+     * Load the translations of the globs in order into a configured `Y10n`
+     *
+     * An error is returned if any of the patterns are invalid, or any of the files can't be read or
+     * deserialized.
+     */
+    pub fn build(self) -> Result<Y10n, Error> {
+        let mut y10n = self.configured();
+        for pattern in self.globs.iter() {
+            y10n.load_glob(pattern, self.layout, &self.options, self.key_fn(), None)?;
+        }
+        Ok(y10n)
+    }
+
+    /**
+     * Load the translations of the globs in order into a configured `Y10n`, skipping any files
+     * which fail to load, see `Y10n::from_glob_lenient`
+     */
+    pub fn build_lenient(self) -> (Y10n, Vec<(PathBuf, Error)>) {
+        let mut y10n = self.configured();
+        let mut failures = vec![];
+
+        for pattern in self.globs.iter() {
+            let loaded = y10n.load_glob(
+                pattern,
+                self.layout,
+                &self.options,
+                self.key_fn(),
+                Some(&mut failures),
+            );
+            if let Err(e) = loaded {
+                failures.push((PathBuf::from(pattern), e));
+            }
+        }
+        (y10n, failures)
+    }
+
+    /**
+     * Return the closure deriving language keys, if one has been set
+     */
+    fn key_fn(&self) -> Option<&KeyFn> {
+        self.key_fn.as_ref().map(|key_fn| key_fn.0.as_ref())
+    }
+
+    /**
+     * Create an empty `Y10n` with the configured settings
+     */
+    fn configured(&self) -> Y10n {
+        let mut y10n = Y10n::new();
+        y10n.default_language = self.default_language.clone();
+        y10n.merge_strategy = self.merge_strategy;
+        y10n.max_depth = self.max_depth;
+        y10n.strict_regions = self.strict_regions;
+        y10n.whitespace = self.whitespace;
+        for key in self.fallback_only.iter() {
+            y10n.set_fallback_only(key);
+        }
+        y10n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_accept_language;
+
+    #[test]
+    fn build_configured() {
+        let y10n = Y10n::builder()
+            .glob("fixtures/globs/common/*.yml")
+            .glob("fixtures/globs/pages/*.yml")
+            .default_language("de")
+            .fallback_only("en")
+            .build()
+            .expect("Failed to load translations");
+
+        let wildcard = parse_accept_language("*");
+        assert_eq!(Some("Anmelden"), y10n.lookup("login", &wildcard));
+        assert_eq!(
+            Some("Sign in to continue"),
+            y10n.lookup("login", &["en".into()])
+        );
+        assert_eq!(y10n.negotiate("en").len(), 0);
+    }
+
+    #[test]
+    fn build_merge_strategy() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        std::fs::create_dir(dir.path().join("b")).unwrap();
+        std::fs::write(dir.path().join("a/en.yml"), "menu: [open]").unwrap();
+        std::fs::write(dir.path().join("b/en.yml"), "menu: [close]").unwrap();
+
+        let pattern = dir.path().join("*/en.yml");
+        let y10n = Y10n::builder()
+            .glob(&pattern.to_string_lossy())
+            .merge_strategy(MergeStrategy::ConcatSequences)
+            .build()
+            .expect("Failed to load translations");
+        let menu = y10n.localize(&["en".into()]);
+        assert_eq!(menu["menu"].as_sequence().map(|seq| seq.len()), Some(2));
+    }
+
+    #[test]
+    fn build_empty() {
+        let y10n = Y10nBuilder::new().build().expect("Failed to build");
+        assert!(y10n.languages().is_empty());
+    }
+
+    #[test]
+    fn build_invalid_glob() {
+        assert!(matches!(
+            Y10n::builder().glob("l10n/[").build(),
+            Err(Error::Pattern { .. })
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+//!
+//! A least-recently-used cache of merged translations, keyed by the chain of language keys which
+//! were merged
+//!
+
+use std::sync::{Arc, Mutex};
+
+/// The number of merged results kept by default
+pub(crate) const DEFAULT_CAPACITY: usize = 64;
+
+type Entry = (Vec<String>, Arc<serde_yaml::Value>);
+
+pub(crate) struct Cache {
+    capacity: usize,
+    /// Entries ordered from least to most recently used
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl Cache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(vec![]),
+        }
+    }
+
+    /**
+     * Return the cached value for the key, marking it as the most recently used
+     */
+    pub(crate) fn get(&self, key: &[String]) -> Option<Arc<serde_yaml::Value>> {
+        let mut entries = self.entries.lock().unwrap();
+        let index = entries.iter().position(|(k, _)| k == key)?;
+        let entry = entries.remove(index);
+        let value = entry.1.clone();
+        entries.push(entry);
+        Some(value)
+    }
+
+    /**
+     * Insert a value, evicting the least recently used entries beyond the capacity
+     */
+    pub(crate) fn insert(&self, key: Vec<String>, value: Arc<serde_yaml::Value>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(k, _)| k != &key);
+        entries.push((key, value));
+
+        let excess = entries.len().saturating_sub(self.capacity);
+        entries.drain(..excess);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let entries = self.entries.get_mut().unwrap();
+        let excess = entries.len().saturating_sub(capacity);
+        entries.drain(..excess);
+    }
+}
+
+/// Clones start with an empty cache, since the translations of the clone may diverge
+impl Clone for Cache {
+    fn clone(&self) -> Self {
+        Self::new(self.capacity)
+    }
+}
+
+impl std::fmt::Debug for Cache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(k: &str) -> Vec<String> {
+        vec![k.to_string()]
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = Cache::new(2);
+        cache.insert(key("a"), Arc::new("a".into()));
+        cache.insert(key("b"), Arc::new("b".into()));
+        assert!(cache.get(&key("a")).is_some());
+
+        cache.insert(key("c"), Arc::new("c".into()));
+        assert_eq!(2, cache.len());
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+        assert!(cache.get(&key("c")).is_some());
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let cache = Cache::new(0);
+        cache.insert(key("a"), Arc::new("a".into()));
+        assert_eq!(0, cache.len());
+    }
+}
@@ -0,0 +1,110 @@
+//!
+//! Detection of keys which are declared more than once within a single YAML document
+//!
+//! serde_yaml silently keeps the last value for a duplicated key, so this walks the raw parser
+//! events instead of the deserialized value.
+//!
+
+use std::collections::HashSet;
+use yaml_rust::parser::{Event, EventReceiver, Parser};
+
+enum Frame {
+    /// A mapping along with the keys seen so far, and the key whose value is being parsed
+    Mapping {
+        seen: HashSet<String>,
+        key: Option<String>,
+    },
+    Sequence,
+}
+
+#[derive(Default)]
+struct Finder {
+    stack: Vec<Frame>,
+    duplicates: Vec<String>,
+}
+
+impl Finder {
+    /**
+     * The dotted path of the keys currently being parsed
+     */
+    fn path(&self) -> Vec<String> {
+        self.stack
+            .iter()
+            .filter_map(|frame| match frame {
+                Frame::Mapping { key, .. } => key.clone(),
+                Frame::Sequence => None,
+            })
+            .collect()
+    }
+
+    /**
+     * Record that a complete node was parsed inside of the current collection
+     */
+    fn node(&mut self, scalar: Option<String>) {
+        let mut path = self.path();
+
+        if let Some(Frame::Mapping { seen, key }) = self.stack.last_mut() {
+            if key.is_some() {
+                *key = None;
+            } else {
+                let name = scalar.unwrap_or_else(|| "?".to_string());
+                if !seen.insert(name.clone()) {
+                    path.push(name.clone());
+                    self.duplicates.push(path.join("."));
+                }
+                *key = Some(name);
+            }
+        }
+    }
+}
+
+impl EventReceiver for Finder {
+    fn on_event(&mut self, event: Event) {
+        match event {
+            Event::Scalar(value, _, _, _) => self.node(Some(value)),
+            Event::Alias(_) => self.node(None),
+            Event::MappingStart(_) => self.stack.push(Frame::Mapping {
+                seen: HashSet::new(),
+                key: None,
+            }),
+            Event::SequenceStart(_) => self.stack.push(Frame::Sequence),
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.node(None);
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * Return the dotted paths of every key which is declared more than once in the document
+ *
+ * Documents which fail to parse report no duplicates, leaving the error to be reported when the
+ * document is deserialized.
+ */
+pub(crate) fn find(source: &str) -> Vec<String> {
+    let mut finder = Finder::default();
+    let mut parser = Parser::new(source.chars());
+
+    match parser.load(&mut finder, false) {
+        Ok(_) => finder.duplicates,
+        Err(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_duplicates() {
+        assert!(find("a: 1\nb:\n  a: 2\nc: [1, 1]").is_empty());
+    }
+
+    #[test]
+    fn nested_duplicates() {
+        let source = "greeting: hi\nmenu:\n  open: Open\n  open: Opened\ngreeting: hello";
+        assert_eq!(find(source), vec!["menu.open", "greeting"]);
+    }
+}
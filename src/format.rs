@@ -0,0 +1,211 @@
+//!
+//! Formatting of numbers and common phrases containing them, e.g. "1,234 of 10,000"
+//!
+//! The built-in rules cover English, German and French to start with, other languages return
+//! `None` so that callers can fall back to their own formatting.
+//!
+
+use crate::Language;
+
+/// The number formatting conventions of a language
+struct Rules {
+    /// The language code, e.g. `en`
+    code: &'static str,
+    /// The separator between groups of thousands
+    thousands: &'static str,
+    /// The separator between the integer and the fraction
+    decimal: &'static str,
+    /// The connective of progress strings
+    connective: &'static str,
+}
+
+const RULES: &[Rules] = &[
+    Rules {
+        code: "en",
+        thousands: ",",
+        decimal: ".",
+        connective: "of",
+    },
+    Rules {
+        code: "de",
+        thousands: ".",
+        decimal: ",",
+        connective: "von",
+    },
+    Rules {
+        code: "fr",
+        thousands: "\u{202f}",
+        decimal: ",",
+        connective: "sur",
+    },
+];
+
+/**
+ * Render a number with the thousands and decimal separators of the first supported language,
+ * e.g. `1234567.89` is rendered as "1,234,567.89" in English, "1.234.567,89" in German, and
+ * "1 234 567,89" in French, which groups thousands with a narrow no-break space
+ *
+ * The fraction is rendered with as many digits as needed to represent the value, see
+ * `Y10n::format_number` for overriding the separators in the translation files.
+ *
+ * ```rust
+ * use y10n::format::number;
+ * assert_eq!(number(1234567.89, &["de".into()]).as_deref(), Some("1.234.567,89"));
+ * ```
+ *
+ * Returns `None` when none of the languages are supported.
+ */
+pub fn number(value: f64, languages: &[Language]) -> Option<String> {
+    let rules = rules(languages)?;
+    Some(with_separators(value, rules.thousands, rules.decimal))
+}
+
+/**
+ * Return the built-in thousands and decimal separators of the language code
+ */
+pub(crate) fn separators(code: &str) -> Option<(&'static str, &'static str)> {
+    RULES
+        .iter()
+        .find(|rules| rules.code == code)
+        .map(|rules| (rules.thousands, rules.decimal))
+}
+
+/**
+ * Render a number with the given thousands and decimal separators
+ *
+ * Values which aren't finite, such as `NaN`, are rendered as they are by `Display`.
+ */
+pub(crate) fn with_separators(value: f64, thousands: &str, decimal: &str) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    let digits = value.abs().to_string();
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits.as_str(), None),
+    };
+
+    let mut formatted = String::new();
+    if value < 0.0 {
+        formatted.push('-');
+    }
+    formatted.push_str(&group_digits(integer, thousands));
+    if let Some(fraction) = fraction {
+        formatted.push_str(decimal);
+        formatted.push_str(fraction);
+    }
+    formatted
+}
+
+/**
+ * Render the progress through a number of items, e.g. "3 of 10" or "3 von 10", using the rules
+ * of the first supported language
+ *
+ * Numbers are grouped by thousands with the separator of the language, so 1234 of 10000 is
+ * rendered as "1,234 of 10,000" in English and "1.234 von 10.000" in German.
+ *
+ * ```rust
+ * use y10n::format::progress;
+ * assert_eq!(progress(3, 10, &["de".into()]).as_deref(), Some("3 von 10"));
+ * ```
+ *
+ * Returns `None` when none of the languages are supported.
+ */
+pub fn progress(current: u64, total: u64, languages: &[Language]) -> Option<String> {
+    let rules = rules(languages)?;
+    Some(format!(
+        "{} {} {}",
+        group_digits(&current.to_string(), rules.thousands),
+        rules.connective,
+        group_digits(&total.to_string(), rules.thousands)
+    ))
+}
+
+/**
+ * Find the rules of the first supported language
+ */
+fn rules(languages: &[Language]) -> Option<&'static Rules> {
+    languages
+        .iter()
+        .find_map(|lang| RULES.iter().find(|rules| rules.code == lang.code))
+}
+
+/**
+ * Group a string of digits by thousands with the separator
+ */
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut grouped = String::new();
+
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_en() {
+        let languages: Vec<Language> = vec!["en".into()];
+        assert_eq!(progress(3, 10, &languages).as_deref(), Some("3 of 10"));
+        assert_eq!(
+            progress(1234, 10000, &languages).as_deref(),
+            Some("1,234 of 10,000")
+        );
+    }
+
+    #[test]
+    fn progress_de() {
+        let languages: Vec<Language> = crate::parse_accept_language("de-AT");
+        assert_eq!(progress(3, 10, &languages).as_deref(), Some("3 von 10"));
+        assert_eq!(
+            progress(1234, 1000000, &languages).as_deref(),
+            Some("1.234 von 1.000.000")
+        );
+    }
+
+    #[test]
+    fn progress_fr() {
+        let languages: Vec<Language> = vec!["fr".into()];
+        assert_eq!(
+            progress(1234, 10000, &languages).as_deref(),
+            Some("1\u{202f}234 sur 10\u{202f}000")
+        );
+    }
+
+    #[test]
+    fn number_locales() {
+        let en: Vec<Language> = vec!["en".into()];
+        let de: Vec<Language> = vec!["de".into()];
+        let fr: Vec<Language> = vec!["fr".into()];
+        assert_eq!(number(1234567.89, &en).as_deref(), Some("1,234,567.89"));
+        assert_eq!(number(1234567.89, &de).as_deref(), Some("1.234.567,89"));
+        assert_eq!(
+            number(1234567.89, &fr).as_deref(),
+            Some("1\u{202f}234\u{202f}567,89")
+        );
+        assert_eq!(number(-1234.5, &de).as_deref(), Some("-1.234,5"));
+        assert_eq!(number(999.0, &en).as_deref(), Some("999"));
+        assert_eq!(number(0.25, &en).as_deref(), Some("0.25"));
+        assert_eq!(number(1.0, &["ja".into()]), None);
+    }
+
+    #[test]
+    fn number_not_finite() {
+        assert_eq!(with_separators(f64::NAN, ",", "."), "NaN");
+        assert_eq!(with_separators(f64::NEG_INFINITY, ",", "."), "-inf");
+    }
+
+    #[test]
+    fn progress_fallback() {
+        let languages: Vec<Language> = vec!["ja".into(), "de".into()];
+        assert_eq!(progress(0, 999, &languages).as_deref(), Some("0 von 999"));
+        assert_eq!(progress(1, 2, &["ja".into()]), None);
+    }
+}
@@ -0,0 +1,254 @@
+//!
+//! Parsing of gettext `.po` files into translations, enabled with the `gettext` feature
+//!
+//! Each translated message becomes a string keyed by its `msgid`, or by `msgctxt.msgid` when the
+//! message has a context. Untranslated messages with an empty `msgstr` are skipped so that the
+//! strings of fallback languages are used for them instead.
+//!
+//! Messages with plural forms and two `msgstr[n]` translations, as used by English and German
+//! among many others, become a mapping of the `one` and `other` plural branches which can be
+//! selected with `Y10n::pluralize`. Messages with any other number of plural forms become a
+//! sequence of the forms in the order of the file.
+//!
+
+use crate::Error;
+use serde_yaml::{Mapping, Value};
+
+/**
+ * A message which is being parsed
+ */
+#[derive(Default)]
+struct Message {
+    context: Option<String>,
+    id: Option<String>,
+    plural: bool,
+    translations: Vec<(usize, String)>,
+}
+
+/**
+ * The part of a message which continuation lines are appended to
+ */
+enum Field {
+    Context,
+    Id,
+    Plural,
+    Translation,
+}
+
+/**
+ * Parse the source of a `.po` file into a mapping of translated messages
+ */
+pub(crate) fn parse(source: &str) -> Result<Value, Error> {
+    let mut messages = Mapping::new();
+    let mut message = Message::default();
+    let mut field: Option<Field> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        let invalid = |reason: &str| Error::Gettext {
+            line: number + 1,
+            reason: reason.to_string(),
+        };
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('"') {
+            let value = unquote(line).ok_or_else(|| invalid("unterminated string"))?;
+            match field {
+                Some(Field::Context) => message.context.get_or_insert_with(String::new),
+                Some(Field::Id) => message.id.get_or_insert_with(String::new),
+                Some(Field::Plural) => continue,
+                Some(Field::Translation) => match message.translations.last_mut() {
+                    Some((_, translation)) => translation,
+                    None => return Err(invalid("string without a keyword")),
+                },
+                None => return Err(invalid("string without a keyword")),
+            }
+            .push_str(&value);
+            continue;
+        }
+
+        let (keyword, rest) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| invalid("expected a keyword and a string"))?;
+        let value = unquote(rest.trim()).ok_or_else(|| invalid("unterminated string"))?;
+
+        // A new message starts with its context or id once the previous one has been translated
+        if matches!(keyword, "msgctxt" | "msgid") && !message.translations.is_empty() {
+            insert(&mut messages, std::mem::take(&mut message));
+        }
+
+        field = Some(match keyword {
+            "msgctxt" => {
+                message.context = Some(value);
+                Field::Context
+            }
+            "msgid" => {
+                message.id = Some(value);
+                Field::Id
+            }
+            "msgid_plural" => {
+                message.plural = true;
+                Field::Plural
+            }
+            "msgstr" => {
+                message.translations.push((0, value));
+                Field::Translation
+            }
+            keyword => {
+                let index = keyword
+                    .strip_prefix("msgstr[")
+                    .and_then(|index| index.strip_suffix(']'))
+                    .and_then(|index| index.parse().ok())
+                    .ok_or_else(|| invalid(&format!("unknown keyword {}", keyword)))?;
+                message.translations.push((index, value));
+                Field::Translation
+            }
+        });
+    }
+    insert(&mut messages, message);
+
+    Ok(Value::Mapping(messages))
+}
+
+/**
+ * Insert a parsed message into the messages, unless it's the header or untranslated
+ */
+fn insert(messages: &mut Mapping, mut message: Message) {
+    let id = match message.id {
+        Some(id) if !id.is_empty() => id,
+        _ => return,
+    };
+    if message.translations.iter().all(|(_, t)| t.is_empty()) {
+        return;
+    }
+
+    let key = match message.context {
+        Some(context) => format!("{}.{}", context, id),
+        None => id,
+    };
+
+    let value = if message.plural {
+        message.translations.sort_by_key(|(index, _)| *index);
+        let mut forms = message
+            .translations
+            .into_iter()
+            .map(|(_, t)| Value::from(t));
+        if forms.len() == 2 {
+            let mut branches = Mapping::new();
+            branches.insert("one".into(), forms.next().unwrap());
+            branches.insert("other".into(), forms.next().unwrap());
+            Value::Mapping(branches)
+        } else {
+            Value::Sequence(forms.collect())
+        }
+    } else {
+        Value::from(message.translations.swap_remove(0).1)
+    };
+    messages.insert(key.into(), value);
+}
+
+/**
+ * Return the contents of a double quoted string with its escape sequences resolved
+ */
+fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => unquoted.push('\n'),
+            't' => unquoted.push('\t'),
+            'r' => unquoted.push('\r'),
+            other => unquoted.push(other),
+        }
+    }
+    Some(unquoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_messages() {
+        let source = r#"
+# A header comment
+msgid ""
+msgstr ""
+"Language: de\n"
+"Plural-Forms: nplurals=2; plural=(n != 1);\n"
+
+#: src/main.rs:12
+msgid "Hello world"
+msgstr "Hallo Welt"
+
+msgid "Untranslated"
+msgstr ""
+
+msgctxt "menu"
+msgid "Open"
+msgstr "Öffnen"
+
+msgid ""
+"A long "
+"message"
+msgstr "Eine lange "
+"Nachricht mit \"Zitat\""
+
+msgid "{{count}} file"
+msgid_plural "{{count}} files"
+msgstr[0] "{{count}} Datei"
+msgstr[1] "{{count}} Dateien"
+"#;
+        let value = parse(source).expect("Failed to parse");
+        assert_eq!(value["Hello world"].as_str(), Some("Hallo Welt"));
+        assert!(value.get("Untranslated").is_none());
+        assert!(value.get("").is_none());
+        assert_eq!(value["menu.Open"].as_str(), Some("Öffnen"));
+        assert_eq!(
+            value["A long message"].as_str(),
+            Some("Eine lange Nachricht mit \"Zitat\"")
+        );
+        assert_eq!(
+            value["{{count}} file"]["other"].as_str(),
+            Some("{{count}} Dateien")
+        );
+    }
+
+    #[test]
+    fn parse_three_forms() {
+        let source = r#"
+msgid "file"
+msgid_plural "files"
+msgstr[0] "plik"
+msgstr[2] "plików"
+msgstr[1] "pliki"
+"#;
+        let value = parse(source).expect("Failed to parse");
+        let forms: Vec<&str> = value["file"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .filter_map(|form| form.as_str())
+            .collect();
+        assert_eq!(forms, vec!["plik", "pliki", "plików"]);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        match parse("msgid \"ok\"\nmsgstr \"unterminated\n") {
+            Err(Error::Gettext { line, .. }) => assert_eq!(line, 2),
+            other => panic!("Expected a gettext error, got {:?}", other),
+        }
+        assert!(parse("\"orphan\"").is_err());
+        assert!(parse("msgfoo \"bar\"").is_err());
+    }
+}
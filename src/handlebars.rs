@@ -1,13 +1,27 @@
-
-use handlebars::{Context, Helper, HelperDef, HelperResult, Output, RenderContext};
+use handlebars::template::TemplateElement;
+use handlebars::{
+    Context, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError, Renderable,
+    StringOutput, Template,
+};
 /// The handlebars module has the optional Handlebars support for Y10n which can
 /// be enabled with the `hb` feature
 use log::*;
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 pub use crate::{Language, Y10n};
 pub use handlebars::Handlebars;
 
+/// The maximum number of nested `t` invocations allowed while rendering a single
+/// localization string, which guards against strings which reference themselves
+const MAX_DEPTH: usize = 16;
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
 ///  This helper ensures that the `t` helper inside handlebars can be used
 ///  properly for resolving Y10n values
 ///
@@ -27,19 +41,115 @@ pub use handlebars::Handlebars;
 ///  This will look up the `greeting` string and interpolate the current context's
 ///  `user` value into the `who placeholder.
 ///
+///  When a `count` keyword argument is given and the localization string is a
+///  mapping of plural branches, the branch for the count is selected (see
+///  `Y10n::pluralize`) and then interpolated with all of the keyword arguments,
+///  including `count` itself:
+///
+///  ```yaml
+///  ---
+///  items:
+///    one: "{{count}} item"
+///    other: "{{count}} items"
+///  ```
+///
+///  Strings with ICU plural arguments, such as
+///  `"{count, plural, one {# item} other {# items}}"`, are formatted for the
+///  `count` in the same way.
+///
+///  Copy which differs between devices can be written as a mapping of `mobile`,
+///  `desktop`, and `other` branches, which are selected with the `form_factor`
+///  keyword argument, falling back to `other`:
+///
+///  ```yaml
+///  ---
+///  action:
+///    mobile: "Tap to continue"
+///    desktop: "Click to continue"
+///    other: "Select to continue"
+///  ```
+///
+///  `{{t "action" form_factor="mobile"}}`
+///
+///  When the localization string is missing in all of the preferred languages,
+///  the `default` keyword argument is written in its place, with the `{{name}}`
+///  placeholders of the other keyword arguments interpolated (and escaped) just
+///  the same:
+///
+///  `{{t "greeting" default="Hello {{who}}" who=user}}`
+///
+///  The default is never compiled as a template, so it may safely come from the
+///  template's context, and anything else within braces is written as-is.
+///
+///  Without a `default` nothing is written, unless the helper has been created
+///  `with_missing_markers`.
+///
+///  Keyword arguments keep their structure, so objects from the template's
+///  context can be passed to the helper and their fields referenced with dotted
+///  paths in the localization string:
+///
+///  ```yaml
+///  ---
+///  welcome: "Welcome {{user.name}}"
+///  ```
+///
+///  `{{t "welcome" user=current_user}}`
+///
+///  Keyword arguments are evaluated by Handlebars before the helper runs, so a
+///  variable can itself be a localized string by using a subexpression:
+///
+///  `{{t "welcome" role=(t "roles.admin")}}`
+///
+///  Keyword arguments are HTML-escaped with the escape function of the
+///  `Handlebars` registry when they're interpolated, just like any other
+///  `{{variable}}`, so untrusted input can safely be passed to the helper. The
+///  localization string itself is trusted and may contain markup, which is
+///  written out unescaped. When the arguments are trusted markup as well, the
+///  `safe=true` keyword argument disables escaping for that invocation:
+///
+///  `{{t "terms" link=terms_link safe=true}}`
+///
+///  The string is then compiled with every `{{variable}}` treated like a
+///  `{{{variable}}}`, and cached separately from its escaping template.
+///
+///  Keyword arguments are otherwise interpolated as-is, so numbers and dates which have
+///  already been formatted by the application (e.g. `amount="1.234,56"`) appear
+///  unchanged in the output. Formatting helpers such as `spellout` are opt-in
+///  and are only applied when registered and used explicitly.
+///
 ///  The helper can be registered with:
 ///
 ///  ```rust
 ///  use y10n::handlebars::*;
-///  let y10n = Y10n::from_glob("l10n/*.yml");
+///  let y10n = Y10n::from_glob("l10n/*.yml").unwrap();
 ///  let languages: Vec<Language> = vec!["en".into()];
 ///  let mut hb = Handlebars::new();
 ///  hb.register_helper("t", Box::new(HandlebarsHelper::new(&y10n, languages)));
 ///  ```
+///
+///  The localization string is rendered with the same `Handlebars` registry the
+///  helper is registered with, so any other registered helpers (including `t`
+///  itself) can be used inside of localization strings.
+///
+///  Localization strings are compiled into templates the first time they are
+///  rendered and cached by the helper, `prewarm` can be used to compile them ahead
+///  of time.
 #[derive(Clone, Debug)]
 pub struct HandlebarsHelper<'a> {
-    y10n: &'a Y10n,
+    y10n: Source<'a>,
     languages: Vec<Language>,
+    prefix: Option<String>,
+    missing_markers: bool,
+    templates: Arc<RwLock<HashMap<String, Arc<Template>>>>,
+    unescaped_templates: Arc<RwLock<HashMap<String, Arc<Template>>>>,
+}
+
+/// The Y10n which a helper resolves localization strings from, which is shared
+/// when the helper has been created without a pre-existing Y10n
+#[derive(Clone, Debug)]
+enum Source<'a> {
+    Borrowed(&'a Y10n),
+    Shared(Arc<Y10n>),
 }
 
 impl<'a> HandlebarsHelper<'a> {
@@ -47,11 +157,457 @@ impl<'a> HandlebarsHelper<'a> {
     /// Instantiation of the HandlebarsHelper should come with a pre-existing
     /// Y10n struct and an array of preferred languages for rendering the localization
     /// strings in the Handlebars templates
+    pub fn new(y10n: &'a Y10n, languages: Vec<Language>) -> Self {
+        Self {
+            y10n: Source::Borrowed(y10n),
+            languages,
+            prefix: None,
+            missing_markers: false,
+            templates: Arc::new(RwLock::new(HashMap::new())),
+            unescaped_templates: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    ///
+    /// Prepend the prefix to every key looked up by the helper, so that component
+    /// templates can use short keys, e.g. `{{t "title"}}` resolves
+    /// `components.card.title` with the prefix `components.card`
+    ///
+    /// ```rust
+    /// use y10n::handlebars::*;
+    /// let y10n = Y10n::from_glob("l10n/*.yml").unwrap();
+    /// let helper = HandlebarsHelper::new(&y10n, vec!["en".into()]).with_prefix("components.card");
+    /// ```
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.trim_end_matches('.').to_string());
+        self
+    }
+
+    ///
+    /// Write a visible `[[key]]` marker in place of localization strings which are
+    /// missing in all of the preferred languages and have no `default`, which makes
+    /// untranslated strings easy to spot while developing or reviewing templates
+    ///
+    /// ```rust
+    /// use y10n::handlebars::*;
+    /// let y10n = Y10n::from_glob("l10n/*.yml").unwrap();
+    /// let mut hb = Handlebars::new();
+    /// let helper = HandlebarsHelper::new(&y10n, vec!["en".into()]).with_missing_markers(true);
+    /// hb.register_helper("t", Box::new(helper));
+    /// let rendered = hb.render_template(r#"{{t "unknown"}}"#, &()).unwrap();
+    /// assert_eq!(rendered, "[[unknown]]");
+    /// ```
+    pub fn with_missing_markers(mut self, enabled: bool) -> Self {
+        self.missing_markers = enabled;
+        self
+    }
+
+    ///
+    /// Create a helper which shares ownership of the Y10n, which allows it to be
+    /// registered on a long-lived `Handlebars` registry such as in the state of a
+    /// web application
+    ///
+    /// When the helper is created without any languages, the languages set for
+    /// the current thread with `y10n::with_languages` are used, so that a single
+    /// registered helper can render each request in its own languages:
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use y10n::handlebars::*;
+    ///
+    /// struct AppState {
+    ///     handlebars: Handlebars<'static>,
+    /// }
+    ///
+    /// let y10n = Arc::new(Y10n::from_glob("l10n/*.yml").unwrap());
+    /// let mut handlebars = Handlebars::new();
+    /// handlebars.register_helper("t", Box::new(HandlebarsHelper::from_arc(y10n.clone(), vec![])));
+    /// let state = Arc::new(AppState { handlebars });
+    ///
+    /// // In a request handler
+    /// let languages = y10n.negotiate("de-DE,en;q=0.5");
+    /// let rendered = y10n::with_languages(&languages, || {
+    ///     state.handlebars.render_template(r#"{{t "greeting"}}"#, &())
+    /// });
+    /// assert_eq!(rendered.unwrap(), "moin moin");
+    /// ```
+    pub fn from_arc(y10n: Arc<Y10n>, languages: Vec<Language>) -> HandlebarsHelper<'static> {
+        HandlebarsHelper {
+            y10n: Source::Shared(y10n),
+            languages,
+            prefix: None,
+            missing_markers: false,
+            templates: Arc::new(RwLock::new(HashMap::new())),
+            unescaped_templates: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    ///
+    /// Return the Y10n which localization strings are resolved from
+    fn y10n(&self) -> &Y10n {
+        match &self.y10n {
+            Source::Borrowed(y10n) => y10n,
+            Source::Shared(y10n) => y10n,
+        }
+    }
+
+    ///
+    /// Compile and cache the templates for the given localization keys ahead of
+    /// time, so that the first render of each of them doesn't pay the parsing cost
+    ///
+    /// Every branch of plural mappings is compiled as well.
+    pub fn prewarm(&self, keys: &[&str]) -> Result<(), RenderError> {
+        for key in keys {
+            if let Some((_, value)) = self.y10n().lookup_entry(key, &self.languages) {
+                let sources: Vec<&str> = match value.as_mapping() {
+                    Some(branches) => branches.iter().filter_map(|(_, v)| v.as_str()).collect(),
+                    None => value.as_str().into_iter().collect(),
+                };
+
+                for source in sources {
+                    self.template(source, true)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Return the compiled template for the source, compiling it if it hasn't been
+    /// cached yet
+    ///
+    /// Without `escape` the `{{variable}}` expressions of the template aren't
+    /// escaped, see `safe=true`.
+    fn template(&self, source: &str, escape: bool) -> Result<Arc<Template>, RenderError> {
+        let cache = if escape {
+            &self.templates
+        } else {
+            &self.unescaped_templates
+        };
+        if let Some(template) = cache.read().unwrap().get(source) {
+            return Ok(template.clone());
+        }
+
+        trace!("Compiling localization string: {}", source);
+        let mut template = Template::compile(source)?;
+        if !escape {
+            unescape(&mut template);
+        }
+        let template = Arc::new(template);
+        cache
+            .write()
+            .unwrap()
+            .insert(source.to_string(), template.clone());
+        Ok(template)
+    }
+
+    ///
+    /// Resolve the localization string for the helper invocation, selecting a
+    /// form factor or plural branch when the relevant keyword arguments are given
+    fn resolve(&self, h: &Helper, param: &str) -> Option<Cow<'_, str>> {
+        if let Some(form_factor) = h.hash_get("form_factor") {
+            let form_factor = form_factor.render();
+            let selected = self
+                .y10n()
+                .lookup_entry(param, &self.languages)
+                .and_then(|(_, value)| value.as_mapping())
+                .and_then(|branches| {
+                    branches
+                        .get(&form_factor.as_str().into())
+                        .or_else(|| branches.get(&"other".into()))
+                })
+                .and_then(|branch| branch.as_str());
+
+            if let Some(selected) = selected {
+                return Some(Cow::Borrowed(selected));
+            }
+        }
+
+        let count = h.hash_get("count").and_then(|count| count.value().as_u64());
+        if let Some(count) = count {
+            if let Some(plural) = self.y10n().pluralize(param, count, &self.languages) {
+                return Some(plural);
+            }
+        }
+
+        let translation = self.y10n().translate(param, &self.languages)?;
+        if crate::plural::is_icu_select(&translation) {
+            let args: HashMap<String, String> = h
+                .hash()
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.render()))
+                .collect();
+            return self
+                .y10n()
+                .select(param, &self.languages, &args)
+                .map(Cow::Owned);
+        }
+        Some(translation)
+    }
+
+    ///
+    /// Collect the variables for interpolating the localization string, with the
+    /// keyword arguments taking precedence over the globals of the Y10n
+    fn data(&self, h: &Helper) -> serde_json::Map<String, serde_json::Value> {
+        self.y10n()
+            .globals()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.as_str().into()))
+            .chain(
+                h.hash()
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.value().clone())),
+            )
+            .collect()
+    }
+
+    ///
+    /// Render the localization string with the registry and data
+    fn render(
+        &self,
+        hb: &Handlebars,
+        source: &str,
+        data: &serde_json::Map<String, serde_json::Value>,
+        escape: bool,
+    ) -> Result<String, RenderError> {
+        let template = self.template(source, escape)?;
+        let ctx = Context::wraps(data)?;
+        let mut out = StringOutput::new();
+        template.render(hb, &ctx, &mut RenderContext::new(None), &mut out)?;
+        out.into_string().map_err(RenderError::from)
+    }
+}
+
+///
+/// Whether the helper invocation has disabled escaping with `safe=true`
+fn is_safe(h: &Helper) -> bool {
+    h.hash_get("safe")
+        .is_some_and(|safe| safe.value().as_bool() == Some(true))
+}
+
+///  Turn every `{{expression}}` of the template, including those nested in
+///  blocks, into a `{{{expression}}}` which is written out without escaping
+fn unescape(template: &mut Template) {
+    for element in template.elements.iter_mut() {
+        match element {
+            TemplateElement::Expression(_) => {
+                let placeholder = TemplateElement::Comment(String::new());
+                if let TemplateElement::Expression(ht) = std::mem::replace(element, placeholder) {
+                    *element = TemplateElement::HtmlExpression(ht);
+                }
+            }
+            TemplateElement::HelperBlock(ht) => {
+                ht.template.iter_mut().for_each(unescape);
+                ht.inverse.iter_mut().for_each(unescape);
+            }
+            _ => {}
+        }
+    }
+}
+
+///  Load the translations matching the glob pattern and return a `Handlebars`
+///  registry with the `t` helper registered for the languages negotiated from
+///  the `Accept-Language` header
+///
+///  This is a shortcut for the common setup of `Y10n::from_glob`,
+///  `Y10n::negotiate` and `HandlebarsHelper::new` for applications which don't
+///  need to hold on to the Y10n themselves.
+///
+///  ```rust
+///  let hb = y10n::handlebars::registry("l10n/*.yml", "de-DE, en;q=0.8").unwrap();
+///  let rendered = hb.render_template(r#"{{t "greeting"}}"#, &()).unwrap();
+///  assert_eq!(rendered, "moin moin");
+///  ```
+pub fn registry(pattern: &str, header: &str) -> Result<Handlebars<'static>, crate::Error> {
+    let y10n = Y10n::from_glob(pattern)?;
+    let languages = y10n.negotiate(header);
+    let mut hb = Handlebars::new();
+    hb.register_helper(
+        "t",
+        Box::new(HandlebarsHelper::from_arc(Arc::new(y10n), languages)),
+    );
+    Ok(hb)
+}
+
+///  The `spellout` helper renders a number as words using the rules of the
+///  preferred languages, see `y10n::spellout::spellout`
+///
+///  ```rust
+///  use y10n::handlebars::*;
+///  let mut hb = Handlebars::new();
+///  hb.register_helper("spellout", Box::new(SpelloutHelper::new(vec!["en".into()])));
+///  let rendered = hb.render_template("{{spellout 42}}", &()).unwrap();
+///  assert_eq!(rendered, "forty-two");
+///  ```
+///
+///  Numbers are rendered as digits when none of the languages are supported.
+#[derive(Clone, Debug)]
+pub struct SpelloutHelper {
+    languages: Vec<Language>,
+}
+
+impl SpelloutHelper {
+    ///
+    /// Create a helper which spells out numbers in the preferred languages
+    pub fn new(languages: Vec<Language>) -> Self {
+        Self { languages }
+    }
+}
+
+impl HelperDef for SpelloutHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper,
+        _: &Handlebars,
+        _: &Context,
+        _rc: &mut RenderContext,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let number = h
+            .param(0)
+            .and_then(|param| param.value().as_i64())
+            .ok_or_else(|| RenderError::new("The spellout helper requires an integer"))?;
+
+        match crate::spellout::spellout(number, &self.languages) {
+            Some(words) => out.write(&words)?,
+            None => out.write(&number.to_string())?,
+        }
+        Ok(())
+    }
+}
+
+///  The `num` helper renders a number with the thousands and decimal separators
+///  of the preferred languages, see `Y10n::format_number`
+///
+///  ```rust
+///  use y10n::handlebars::*;
+///  let y10n = Y10n::from_glob("l10n/*.yml").unwrap();
+///  let mut hb = Handlebars::new();
+///  hb.register_helper("num", Box::new(NumberHelper::new(&y10n, vec!["de".into()])));
+///  let rendered = hb.render_template("{{num 1234567.89}}", &()).unwrap();
+///  assert_eq!(rendered, "1.234.567,89");
+///  ```
+#[derive(Clone, Debug)]
+pub struct NumberHelper<'a> {
+    y10n: &'a Y10n,
+    languages: Vec<Language>,
+}
+
+impl<'a> NumberHelper<'a> {
+    ///
+    /// Create a helper which formats numbers for the preferred languages
     pub fn new(y10n: &'a Y10n, languages: Vec<Language>) -> Self {
         Self { y10n, languages }
     }
 }
 
+impl HelperDef for NumberHelper<'_> {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper,
+        _: &Handlebars,
+        _: &Context,
+        _rc: &mut RenderContext,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let number = h
+            .param(0)
+            .and_then(|param| param.value().as_f64())
+            .ok_or_else(|| RenderError::new("The num helper requires a number"))?;
+
+        out.write(&self.y10n.format_number(number, &self.languages))?;
+        Ok(())
+    }
+}
+
+///  The `dir` helper renders the text direction of the first preferred language,
+///  `ltr` or `rtl`, for the `dir` attribute of an HTML element
+///
+///  ```rust
+///  use y10n::handlebars::*;
+///  let mut hb = Handlebars::new();
+///  hb.register_helper("dir", Box::new(DirectionHelper::new(vec!["ar".into()])));
+///  let rendered = hb.render_template(r#"<html dir="{{dir}}">"#, &()).unwrap();
+///  assert_eq!(rendered, r#"<html dir="rtl">"#);
+///  ```
+#[derive(Clone, Debug)]
+pub struct DirectionHelper {
+    languages: Vec<Language>,
+}
+
+impl DirectionHelper {
+    ///
+    /// Create a helper which renders the text direction of the preferred languages
+    pub fn new(languages: Vec<Language>) -> Self {
+        Self { languages }
+    }
+}
+
+impl HelperDef for DirectionHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        _: &Helper,
+        _: &Handlebars,
+        _: &Context,
+        _rc: &mut RenderContext,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let direction = self
+            .languages
+            .first()
+            .map_or(crate::Direction::Ltr, |lang| lang.direction());
+        out.write(&direction.to_string())?;
+        Ok(())
+    }
+}
+
+///  The `region_name` helper renders the display name of a region code in the
+///  preferred languages, see `y10n::regions::region_name`
+///
+///  ```rust
+///  use y10n::handlebars::*;
+///  let mut hb = Handlebars::new();
+///  hb.register_helper("region_name", Box::new(RegionNameHelper::new(vec!["en".into()])));
+///  let rendered = hb.render_template(r#"{{region_name "US"}}"#, &()).unwrap();
+///  assert_eq!(rendered, "United States");
+///  ```
+///
+///  Unknown regions are rendered as the region code itself.
+#[derive(Clone, Debug)]
+pub struct RegionNameHelper {
+    languages: Vec<Language>,
+}
+
+impl RegionNameHelper {
+    ///
+    /// Create a helper which renders region names in the preferred languages
+    pub fn new(languages: Vec<Language>) -> Self {
+        Self { languages }
+    }
+}
+
+impl HelperDef for RegionNameHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper,
+        _: &Handlebars,
+        _: &Context,
+        _rc: &mut RenderContext,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let region = h
+            .param(0)
+            .map(|param| param.render())
+            .ok_or_else(|| RenderError::new("The region_name helper requires a region code"))?;
+
+        match crate::regions::region_name(&region, &self.languages) {
+            Some(name) => out.write(name)?,
+            None => out.write(&region)?,
+        }
+        Ok(())
+    }
+}
+
 impl HelperDef for HandlebarsHelper<'_> {
     fn call<'reg: 'rc, 'rc>(
         &self,
@@ -61,15 +617,38 @@ impl HelperDef for HandlebarsHelper<'_> {
         _rc: &mut RenderContext,
         out: &mut dyn Output,
     ) -> HelperResult {
-        let param = h.param(0).unwrap().render();
+        let mut param = h.param(0).unwrap().render();
+        if let Some(prefix) = &self.prefix {
+            param = format!("{}.{}", prefix, param);
+        }
         trace!("Looking up localization string: {}", param);
 
-        if let Some(buf) = self.y10n.lookup(&param, &self.languages) {
-            let mut data: HashMap<String, String> = HashMap::new();
-            for (key, value) in h.hash() {
-                data.insert(key.to_string(), value.render());
+        if let Some(buf) = self.resolve(h, &param) {
+            let data = self.data(h);
+            let depth = DEPTH.with(|d| d.get());
+            if depth >= MAX_DEPTH {
+                return Err(RenderError::new(format!(
+                    "Exceeded the maximum nesting of localization strings while rendering: {}",
+                    param
+                )));
             }
-            out.write(&hb.render_template(buf, &data)?)?;
+
+            DEPTH.with(|d| d.set(depth + 1));
+            let rendered = self.render(hb, &buf, &data, !is_safe(h));
+            DEPTH.with(|d| d.set(depth));
+            out.write(&rendered?)?;
+        } else if let Some(default) = h.hash_get("default") {
+            let data = serde_json::Value::Object(self.data(h));
+            let safe = is_safe(h);
+            let escape = hb.get_escape_fn();
+            let rendered = crate::interpolate::interpolate(&default.render(), &|name| {
+                let value = crate::interpolate::resolve_json(&data, name)?;
+                Some(if safe { value } else { escape(&value) })
+            });
+            out.write(&rendered)?;
+        } else if self.missing_markers {
+            debug!("Missing localization string: {}", param);
+            out.write(&format!("[[{}]]", param))?;
         }
         Ok(())
     }
@@ -82,7 +661,7 @@ mod tests {
     #[test]
     fn test_handlebars_helper() {
         use handlebars::Handlebars;
-        let y10n = crate::Y10n::from_glob("l10n/*.yml");
+        let y10n = crate::Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
         let languages: Vec<crate::Language> = vec!["en".into()];
         let template = r#"Well that's it. {{t "thankyou" team=team}}"#;
 
@@ -96,4 +675,484 @@ mod tests {
 
         assert_eq!(rendered, "Well that's it. Thanks for playing Foo!");
     }
+
+    fn y10n_from(yaml: &str) -> Y10n {
+        let mut y10n = Y10n::new();
+        y10n.translations.insert(
+            "en".into(),
+            serde_yaml::from_str(yaml).expect("Failed to deserialize YAML"),
+        );
+        y10n
+    }
+
+    /// Create a registry with the `t` helper registered for the English translations of the y10n
+    fn english_registry(y10n: &Y10n) -> Handlebars<'_> {
+        let mut hb = Handlebars::new();
+        hb.register_helper(
+            "t",
+            Box::new(HandlebarsHelper::new(y10n, vec!["en".into()])),
+        );
+        hb
+    }
+
+    /// Render the template with the `t` helper registered for the English translations of the y10n
+    fn try_render_with(
+        y10n: &Y10n,
+        template: &str,
+        data: &serde_json::Value,
+    ) -> Result<String, RenderError> {
+        english_registry(y10n).render_template(template, data)
+    }
+
+    /// Render the template with the `t` helper registered for the English translations in the YAML
+    fn render(yaml: &str, template: &str, data: &serde_json::Value) -> String {
+        try_render_with(&y10n_from(yaml), template, data).expect("Failed to render")
+    }
+
+    #[test]
+    fn test_registered_helpers_in_strings() {
+        use handlebars::handlebars_helper;
+        handlebars_helper!(uppercase: |s: str| s.to_uppercase());
+
+        let y10n = y10n_from("shout: 'Hello {{uppercase who}}'");
+        let mut hb = Handlebars::new();
+        hb.register_helper("uppercase", Box::new(uppercase));
+        hb.register_helper(
+            "t",
+            Box::new(HandlebarsHelper::new(&y10n, vec!["en".into()])),
+        );
+
+        let rendered = hb
+            .render_template(r#"{{t "shout" who="world"}}"#, &())
+            .expect("Failed to render");
+        assert_eq!(rendered, "Hello WORLD");
+    }
+
+    #[test]
+    fn test_plural_interpolation() {
+        let yaml =
+            "items:\n  one: '{{count}} item for {{who}}'\n  other: '{{count}} items, {{who}}!'";
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            render(yaml, r#"{{t "items" count=1 who="you"}}"#, &none),
+            "1 item for you"
+        );
+        assert_eq!(
+            render(yaml, r#"{{t "items" count=5 who="folks"}}"#, &none),
+            "5 items, folks!"
+        );
+    }
+
+    #[test]
+    fn test_icu_plurals() {
+        let yaml =
+            "items: '{count, plural, =0 {No items} one {# item} other {# items for {{who}}}}'";
+        let none = serde_json::Value::Null;
+        assert_eq!(render(yaml, r#"{{t "items" count=0}}"#, &none), "No items");
+        assert_eq!(render(yaml, r#"{{t "items" count=1}}"#, &none), "1 item");
+        assert_eq!(
+            render(yaml, r#"{{t "items" count=42 who="you"}}"#, &none),
+            "42 items for you"
+        );
+    }
+
+    #[test]
+    fn test_prewarm() {
+        let y10n = y10n_from("greeting: 'Hello {{who}}'\nsecret: 'pancakes'");
+        let helper = HandlebarsHelper::new(&y10n, vec!["en".into()]);
+        helper
+            .prewarm(&["greeting", "secret", "missing"])
+            .expect("Failed to prewarm");
+        assert_eq!(2, helper.templates.read().unwrap().len());
+
+        let mut hb = Handlebars::new();
+        hb.register_helper("t", Box::new(helper.clone()));
+        let rendered = hb
+            .render_template(r#"{{t "greeting" who="world"}} {{t "secret"}}"#, &())
+            .expect("Failed to render");
+        assert_eq!(rendered, "Hello world pancakes");
+        assert_eq!(2, helper.templates.read().unwrap().len());
+    }
+
+    #[test]
+    fn test_form_factor() {
+        let yaml =
+            "action:\n  mobile: 'Tap {{what}}'\n  desktop: 'Click {{what}}'\n  other: 'Select {{what}}'";
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "action" form_factor="mobile" what="here"}}"#,
+                &none
+            ),
+            "Tap here"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "action" form_factor="desktop" what="here"}}"#,
+                &none
+            ),
+            "Click here"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "action" form_factor="watch" what="here"}}"#,
+                &none
+            ),
+            "Select here"
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        let yaml =
+            "replied: '{gender, select, male {He} female {She} other {They}} replied to {{who}}'";
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            render(yaml, r#"{{t "replied" gender="male" who="Ferris"}}"#, &none),
+            "He replied to Ferris"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "replied" gender="female" who="Ferris"}}"#,
+                &none
+            ),
+            "She replied to Ferris"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "replied" gender="other" who="Ferris"}}"#,
+                &none
+            ),
+            "They replied to Ferris"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "replied" gender="robot" who="Ferris"}}"#,
+                &none
+            ),
+            "They replied to Ferris"
+        );
+    }
+
+    #[test]
+    fn test_globals() {
+        let mut y10n = y10n_from("help: 'Contact {{support_email}} about {{product}}'");
+        let mut globals = HashMap::new();
+        globals.insert("support_email".to_string(), "help@example.com".to_string());
+        globals.insert("product".to_string(), "y10n".to_string());
+        y10n.set_globals(globals);
+
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            try_render_with(&y10n, r#"{{t "help"}}"#, &none).expect("Failed to render"),
+            "Contact help@example.com about y10n"
+        );
+        assert_eq!(
+            try_render_with(&y10n, r#"{{t "help" product="Ferris"}}"#, &none)
+                .expect("Failed to render"),
+            "Contact help@example.com about Ferris"
+        );
+    }
+
+    #[test]
+    fn test_subexpression_arguments() {
+        let rendered = render(
+            "welcome: 'Welcome, {{role}} {{who}}!'\nroles.admin: 'Administrator'",
+            r#"{{t "welcome" role=(t "roles.admin") who="Ferris"}}"#,
+            &serde_json::Value::Null,
+        );
+        assert_eq!(rendered, "Welcome, Administrator Ferris!");
+    }
+
+    #[test]
+    fn test_spellout_helper() {
+        let mut hb = Handlebars::new();
+        hb.register_helper("spellout", Box::new(SpelloutHelper::new(vec!["en".into()])));
+        let rendered = hb
+            .render_template(
+                "{{spellout 1234}} and {{spellout n}}",
+                &serde_json::json!({"n": 7}),
+            )
+            .expect("Failed to render");
+        assert_eq!(rendered, "one thousand two hundred thirty-four and seven");
+    }
+
+    #[test]
+    fn test_number_helper() {
+        let y10n = y10n_from("greeting: hello");
+        let mut hb = Handlebars::new();
+        hb.register_helper("num", Box::new(NumberHelper::new(&y10n, vec!["en".into()])));
+        let rendered = hb
+            .render_template(
+                "{{num 1234567.89}} and {{num n}}",
+                &serde_json::json!({"n": -12000}),
+            )
+            .expect("Failed to render");
+        assert_eq!(rendered, "1,234,567.89 and -12,000");
+        assert!(hb.render_template(r#"{{num "many"}}"#, &()).is_err());
+    }
+
+    #[test]
+    fn test_direction_helper() {
+        let mut hb = Handlebars::new();
+        hb.register_helper("ar_dir", Box::new(DirectionHelper::new(vec!["ar".into()])));
+        hb.register_helper("en_dir", Box::new(DirectionHelper::new(vec!["en".into()])));
+        hb.register_helper("no_dir", Box::new(DirectionHelper::new(vec![])));
+        let rendered = hb
+            .render_template("{{ar_dir}} {{en_dir}} {{no_dir}}", &())
+            .expect("Failed to render");
+        assert_eq!(rendered, "rtl ltr ltr");
+    }
+
+    #[test]
+    fn test_region_name_helper() {
+        let mut hb = Handlebars::new();
+        hb.register_helper(
+            "en_region",
+            Box::new(RegionNameHelper::new(vec!["en".into()])),
+        );
+        hb.register_helper(
+            "de_region",
+            Box::new(RegionNameHelper::new(vec!["de".into()])),
+        );
+        let rendered = hb
+            .render_template(
+                r#"{{en_region "US"}}, {{de_region "US"}}, {{en_region "XX"}}"#,
+                &(),
+            )
+            .expect("Failed to render");
+        assert_eq!(rendered, "United States, Vereinigte Staaten, XX");
+    }
+
+    #[test]
+    fn test_preformatted_arguments() {
+        let rendered = render(
+            "total: 'Summe: {{amount}}'",
+            r#"{{t "total" amount=amount}}"#,
+            &serde_json::json!({"amount": "1.234,56"}),
+        );
+        assert_eq!(rendered, "Summe: 1.234,56");
+    }
+
+    #[test]
+    fn test_nested_keys() {
+        let rendered = render(
+            "menu:\n  file:\n    open: 'Open {{what}}'",
+            r#"{{t "menu.file.open" what="file"}}"#,
+            &serde_json::Value::Null,
+        );
+        assert_eq!(rendered, "Open file");
+    }
+
+    #[test]
+    fn test_prefix() {
+        let y10n = y10n_from(
+            "components.card.title: 'Card for {{who}}'\ncomponents.card.empty: 'Nothing'\ntitle: 'Page'",
+        );
+        let mut hb = Handlebars::new();
+        hb.register_helper(
+            "t",
+            Box::new(
+                HandlebarsHelper::new(&y10n, vec!["en".into()]).with_prefix("components.card"),
+            ),
+        );
+        hb.register_helper(
+            "page",
+            Box::new(HandlebarsHelper::new(&y10n, vec!["en".into()])),
+        );
+
+        let rendered = hb
+            .render_template(
+                r#"{{page "title"}}: {{t "title" who="you"}}, {{t "empty"}}"#,
+                &(),
+            )
+            .expect("Failed to render");
+        assert_eq!(rendered, "Page: Card for you, Nothing");
+    }
+
+    #[test]
+    fn test_from_arc_per_request_languages() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Y10n>();
+        assert_send_sync::<HandlebarsHelper<'static>>();
+
+        let y10n = Arc::new(Y10n::from_glob("l10n/*.yml").expect("Failed to load translations"));
+        let mut hb = Handlebars::new();
+        hb.register_helper("t", Box::new(HandlebarsHelper::from_arc(y10n, vec![])));
+        let hb = Arc::new(hb);
+
+        let handles: Vec<_> = ["en", "de"]
+            .iter()
+            .map(|code| {
+                let hb = hb.clone();
+                let languages: Vec<Language> = vec![(*code).into()];
+                std::thread::spawn(move || {
+                    crate::with_languages(&languages, || {
+                        hb.render_template(r#"{{t "greeting"}}"#, &())
+                            .expect("Failed to render")
+                    })
+                })
+            })
+            .collect();
+        let rendered: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(rendered, vec!["hello world", "moin moin"]);
+    }
+
+    #[test]
+    fn test_registry() {
+        let hb = registry("l10n/*.yml", "fr, en;q=0.5").expect("Failed to create registry");
+        let rendered = hb
+            .render_template(r#"{{t "thankyou" team="Foo"}}"#, &())
+            .expect("Failed to render");
+        assert_eq!(rendered, "Thanks for playing Foo!");
+    }
+
+    #[test]
+    fn test_registry_bad_pattern() {
+        assert!(registry("l10n/[", "en").is_err());
+    }
+
+    #[test]
+    fn test_default() {
+        let yaml = "greeting: 'Moin {{who}}'";
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "greeting" who="you" default="Hello {{who}}"}}"#,
+                &none
+            ),
+            "Moin you"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "farewell" who="you" default="Bye {{who}}"}}"#,
+                &none
+            ),
+            "Bye you"
+        );
+    }
+
+    #[test]
+    fn test_default_from_context() {
+        let y10n = y10n_from("greeting: 'Moin {{who}}'");
+        let helper = HandlebarsHelper::new(&y10n, vec!["en".into()]);
+        let mut hb = Handlebars::new();
+        hb.register_helper("t", Box::new(helper.clone()));
+
+        let data = serde_json::json!({
+            "input": "{{#if who}}{{who}}{{/if}} {{t \"greeting\"}} {{missing}}",
+        });
+        let rendered = hb
+            .render_template(r#"{{t "farewell" default=input who="<i>you</i>"}}"#, &data)
+            .expect("Failed to render");
+        assert_eq!(
+            rendered,
+            "{{#if who}}&lt;i&gt;you&lt;/i&gt;{{/if}} {{t \"greeting\"}} {{missing}}"
+        );
+        assert!(helper.templates.read().unwrap().is_empty());
+        assert!(helper.unescaped_templates.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_missing_without_default() {
+        let y10n = y10n_from("greeting: 'Moin'");
+        let mut hb = Handlebars::new();
+        hb.register_helper(
+            "t",
+            Box::new(HandlebarsHelper::new(&y10n, vec!["en".into()])),
+        );
+        hb.register_helper(
+            "dev",
+            Box::new(HandlebarsHelper::new(&y10n, vec!["en".into()]).with_missing_markers(true)),
+        );
+
+        let rendered = hb
+            .render_template(r#"[{{t "farewell"}}]"#, &())
+            .expect("Failed to render");
+        assert_eq!(rendered, "[]");
+
+        let rendered = hb
+            .render_template(r#"{{dev "farewell"}} {{dev "greeting"}}"#, &())
+            .expect("Failed to render");
+        assert_eq!(rendered, "[[farewell]] Moin");
+    }
+
+    #[test]
+    fn test_nested_arguments() {
+        let rendered = render(
+            "welcome: 'Welcome {{user.name}}, you have {{user.inbox.unread}} messages, {{who}}'",
+            r#"{{t "welcome" user=current who=nickname}}"#,
+            &serde_json::json!({
+                "current": {"name": "Ferris", "inbox": {"unread": 3}},
+                "nickname": "crab",
+            }),
+        );
+        assert_eq!(rendered, "Welcome Ferris, you have 3 messages, crab");
+    }
+
+    #[test]
+    fn test_escaping() {
+        let y10n = y10n_from("greeting: '<b>Hello</b> {{who}}'");
+        let data = serde_json::json!({"user": "<script>alert(1)</script>"});
+        let hb = english_registry(&y10n);
+        let render = |template: &str| {
+            hb.render_template(template, &data)
+                .expect("Failed to render")
+        };
+
+        assert_eq!(
+            render(r#"{{t "greeting" who=user}}"#),
+            "<b>Hello</b> &lt;script&gt;alert(1)&lt;/script&gt;"
+        );
+        assert_eq!(
+            render(r#"{{t "greeting" who="<i>you</i>" safe=true}}"#),
+            "<b>Hello</b> <i>you</i>"
+        );
+        assert_eq!(
+            render(r#"{{t "greeting" who=user}}"#),
+            "<b>Hello</b> &lt;script&gt;alert(1)&lt;/script&gt;",
+            "Unescaped templates must be cached separately"
+        );
+    }
+
+    #[test]
+    fn test_safe_nested_blocks() {
+        let yaml = "greeting: '{{#if who}}Hello {{who}}{{else}}Hi {{{what}}}{{/if}}'";
+        let none = serde_json::Value::Null;
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "greeting" who="<i>you</i>" safe=true}}"#,
+                &none
+            ),
+            "Hello <i>you</i>"
+        );
+        assert_eq!(
+            render(
+                yaml,
+                r#"{{t "greeting" what="<i>there</i>" safe=true}}"#,
+                &none
+            ),
+            "Hi <i>there</i>"
+        );
+        assert_eq!(
+            render(yaml, r#"{{t "greeting" who="<i>you</i>"}}"#, &none),
+            "Hello &lt;i&gt;you&lt;/i&gt;"
+        );
+    }
+
+    #[test]
+    fn test_recursive_strings() {
+        let y10n = y10n_from("forever: 'and ever {{t \"forever\"}}'");
+        let rendered = try_render_with(&y10n, r#"{{t "forever"}}"#, &serde_json::Value::Null);
+        assert!(rendered.is_err());
+    }
 }
@@ -0,0 +1,131 @@
+//!
+//! A lightweight `{{name}}` placeholder interpolation which doesn't require a templating engine
+//!
+
+use std::collections::HashSet;
+
+/**
+ * Check whether the contents of a `{{ }}` block are a plain variable path such as `who` or
+ * `user.name`, rather than a helper invocation or other template syntax
+ */
+fn is_variable(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+/**
+ * Replace each `{{name}}` placeholder in the template with the value produced by the resolver
+ *
+ * Whitespace inside of the braces is ignored, so `{{ name }}` is equivalent to `{{name}}`.
+ * Placeholders which the resolver returns `None` for, and anything within braces which isn't a
+ * plain variable path, are left in the output untouched. Resolved values are inserted literally
+ * and are never interpolated themselves.
+ */
+pub(crate) fn interpolate(template: &str, resolve: &dyn Fn(&str) -> Option<String>) -> String {
+    scan(template, |name| resolve(name))
+}
+
+/**
+ * Walk the template, replacing each placeholder with the value produced by the callback
+ */
+fn scan<F: FnMut(&str) -> Option<String>>(template: &str, mut resolve: F) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim();
+                match is_variable(name).then(|| resolve(name)).flatten() {
+                    Some(value) => output.push_str(&value),
+                    None => output.push_str(&rest[start..start + end + 4]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/**
+ * Return the names of every `{{name}}` placeholder used in the template
+ */
+pub(crate) fn placeholders(template: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    scan(template, |name| {
+        names.insert(name.to_string());
+        None
+    });
+    names
+}
+
+/**
+ * Resolve a dotted variable path such as `user.name` against a JSON value
+ */
+pub(crate) fn resolve_json(context: &serde_json::Value, path: &str) -> Option<String> {
+    use serde_json::Value;
+
+    let mut value = context;
+    for segment in path.split('.') {
+        value = match value {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => Some(String::new()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn interpolate_variables() {
+        let context = json!({"who": "world", "user": {"name": "Ferris", "age": 7}});
+        let resolve = |name: &str| resolve_json(&context, name);
+
+        assert_eq!(
+            "Hello world, Ferris is 7",
+            interpolate("Hello {{who}}, {{ user.name }} is {{user.age}}", &resolve)
+        );
+    }
+
+    #[test]
+    fn placeholder_names() {
+        let names = placeholders("Hello {{who}}, {{ user.name }} {{uppercase who}} {{who}}");
+        let expected: HashSet<String> =
+            ["who", "user.name"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(expected, names);
+    }
+
+    #[test]
+    fn interpolate_leaves_unknown() {
+        let context = json!({"who": "{{other}}"});
+        let resolve = |name: &str| resolve_json(&context, name);
+
+        assert_eq!(
+            "{{missing}} {{{raw}}} {{uppercase who}} {{other}} {{",
+            interpolate(
+                "{{missing}} {{{raw}}} {{uppercase who}} {{who}} {{",
+                &resolve
+            )
+        );
+    }
+}
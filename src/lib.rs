@@ -8,17 +8,87 @@ extern crate lazy_static;
 
 use glob::glob;
 use log::*;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+mod builder;
+mod cache;
+mod duplicates;
+#[cfg(feature = "gettext")]
+mod gettext;
+mod interpolate;
+mod tags;
+
+/// The format module formats numbers and phrases containing them
+pub mod format;
+
+/// The plural module contains the plural rules used for selecting plural forms
+pub mod plural;
+
+/// The regions module has localized display names for regions
+pub mod regions;
+
+/// The spellout module renders numbers as words
+pub mod spellout;
+
+#[cfg(feature = "watch")]
+/// The watch module can be enabled with the `watch` feature to hot-reload translations
+pub mod watch;
+
+#[cfg(feature = "askama")]
+/// The askama module can be enabled with the `askama` feature
+pub mod askama;
+
+#[cfg(feature = "axum")]
+/// The axum module can be enabled with the `axum` feature
+pub mod axum;
 
 #[cfg(feature = "hb")]
 /// The handlebars module can be enabled with the `hb` feature
 pub mod handlebars;
 
+#[cfg(feature = "tera")]
+/// The tera module can be enabled with the `tera` feature
+pub mod tera;
+
+#[cfg(feature = "macros")]
+/// Validate a static localization key at compile time, enabled with the `macros` feature
+pub use y10n_macros::t_key;
+
+pub use builder::Y10nBuilder;
+
+/// The top-level key of the translations which holds configuration rather than strings
+const CONFIG_KEY: &str = "_config";
+/// The top-level key of the translations which holds the number formatting conventions
+const FORMAT_KEY: &str = "_format";
+
+/// The code of the wildcard language in an `Accept-Language` header, which matches any language
+const WILDCARD: &str = "*";
+
+/// The codes of the languages which are written from right to left
+const RTL_CODES: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ps", "sd", "syr", "ug", "ur", "yi",
+];
+
+/// The default maximum nesting of mappings which is merged or traversed, see `Y10n::set_max_depth`
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
 lazy_static! {
-    static ref LANG_REGEX: regex::Regex =
-        regex::Regex::new(r"(?P<code>\w+)-?(?P<region>\w+)?(;q=(?P<quality>([0-9]*[.])?[0-9]+)?)?")
-            .unwrap();
+    static ref LANG_REGEX: regex::Regex = regex::Regex::new(
+        r"(?P<code>\w+)(-(?P<script>[A-Za-z]{4})\b)?-?(?P<region>\w+)?(;q=(?P<quality>([0-9]*[.])?[0-9]+)?)?"
+    )
+    .unwrap();
+    /// A BCP 47 language tag: a 2-3 letter language, an optional 4 letter script, and an optional
+    /// 2 letter or 3 digit region
+    static ref STRICT_LANG_REGEX: regex::Regex = regex::Regex::new(
+        r"^\s*(?P<code>[A-Za-z]{2,3})(-(?P<script>[A-Za-z]{4}))?(-(?P<region>[A-Za-z]{2}|[0-9]{3}))?\s*(;\s*q=(?P<quality>0(\.[0-9]{0,3})?|1(\.0{0,3})?))?\s*$"
+    )
+    .unwrap();
 }
 
 /**
@@ -27,12 +97,118 @@ lazy_static! {
 #[derive(Clone, Debug)]
 pub struct Y10n {
     translations: HashMap<String, serde_yaml::Value>,
+    missing_key_handler: Option<MissingKeyHandler>,
+    whitespace: Whitespace,
+    humanize_missing_keys: bool,
+    sort_keys: bool,
+    cache: cache::Cache,
+    custom_plural_rules: HashMap<String, plural::PluralRule>,
+    fallback_only: Vec<String>,
+    max_depth: usize,
+    strict_regions: bool,
+    merge_strategy: MergeStrategy,
+    default_language: Option<String>,
+    globals: HashMap<String, String>,
+}
+
+/**
+ * How sequences present in more than one of the merged translations are combined
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MergeStrategy {
+    /// The sequence of the preferred translations replaces the others, so a German list of menu
+    /// items doesn't get English entries appended to it
+    #[default]
+    ReplaceSequences,
+    /// The sequences are concatenated, with the entries of the less preferred translations first
+    ConcatSequences,
+}
+
+/**
+ * How the translation files matched by a glob are laid out on disk, see
+ * `Y10n::from_glob_with_layout`
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Layout {
+    /// Each file holds the translations of one language, named by its file stem, e.g. `en.yml`
+    #[default]
+    FileIsLanguage,
+    /// Each directory holds the translations of one language, named by the directory, and each
+    /// file within it is a namespace named by its file stem, e.g. `en/menu.yml`
+    DirectoryIsLanguage,
+}
+
+/**
+ * The direction which the text of a language is written in, e.g. for the `dir` attribute of an
+ * HTML element
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Direction {
+    /// Left to right, such as English
+    #[default]
+    Ltr,
+    /// Right to left, such as Arabic or Hebrew
+    Rtl,
+}
+
+/**
+ * Format the direction as the value of an HTML `dir` attribute, `ltr` or `rtl`
+ */
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Ltr => write!(f, "ltr"),
+            Direction::Rtl => write!(f, "rtl"),
+        }
+    }
+}
+
+/**
+ * The treatment of leading and trailing whitespace in resolved strings
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Whitespace {
+    /// Return strings exactly as they were written, which is useful for fragments which are
+    /// intentionally concatenated
+    #[default]
+    Preserve,
+    /// Trim leading and trailing whitespace from strings
+    Trim,
+}
+
+/**
+ * The callback invoked by `translate` for keys which no language provides
+ */
+#[derive(Clone)]
+struct MissingKeyHandler(Arc<MissingKeyFn>);
+
+type MissingKeyFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// The callback which derives the language key of a translation file from its path
+type KeyFn = dyn Fn(&Path) -> Option<String> + Send + Sync;
+
+impl std::fmt::Debug for MissingKeyHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MissingKeyHandler")
+    }
 }
 
 impl Y10n {
     fn new() -> Self {
         Self {
             translations: HashMap::default(),
+            missing_key_handler: None,
+            whitespace: Whitespace::default(),
+            humanize_missing_keys: false,
+            sort_keys: true,
+            cache: cache::Cache::new(cache::DEFAULT_CAPACITY),
+            custom_plural_rules: HashMap::new(),
+            fallback_only: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            strict_regions: false,
+            merge_strategy: MergeStrategy::default(),
+            default_language: None,
+            globals: HashMap::new(),
         }
     }
 
@@ -41,36 +217,542 @@ impl Y10n {
      *
      * For example `"l10n/**/*.yml"` will load all the yml files in the `l10n` directory using each
      * file's name (e.g. `en.yml`) to derive it's language key (`en`).
+     *
+     * Regional translations are named with the region after a hyphen, `en-US.yml` is loaded with
+     * the key `en-US`, and are consulted before the translations of their base language (see
+     * `localize`).
+     *
+     * With the `toml` feature enabled, matched files with the `.toml` extension are parsed as
+     * TOML, e.g. `"l10n/**/*.toml"`, and their tables can be looked up with dotted keys just like
+     * nested YAML mappings. Likewise with the `gettext` feature enabled, `.po` files are parsed
+     * with each translated message keyed by its `msgid`.
+     *
+     * Files which share a file stem in different directories are merged, with the strings of the
+     * file matched last winning.
+     *
+     * An error is returned if the pattern is invalid or any of the files can't be read or
+     * deserialized, so that a malformed file doesn't crash the process.
      */
-    pub fn from_glob(pattern: &str) -> Self {
-        let mut this = Self::new();
+    pub fn from_glob(pattern: &str) -> Result<Self, Error> {
+        Self::from_glob_with_options(pattern, &LoadOptions::default())
+    }
+
+    /**
+     * Create a `Y10nBuilder` for configuring the settings of a Y10n instance before its
+     * translations are loaded
+     */
+    pub fn builder() -> Y10nBuilder {
+        Y10nBuilder::new()
+    }
+
+    /**
+     * Create and load a Y10n instance from the yml files in the given glob, applying the given
+     * `LoadOptions` to each file as it is loaded
+     */
+    pub fn from_glob_with_options(pattern: &str, options: &LoadOptions) -> Result<Self, Error> {
+        Self::builder()
+            .glob(pattern)
+            .load_options(options.clone())
+            .build()
+    }
+
+    /**
+     * Create and load a Y10n instance from the yml files in the given glob, which are laid out
+     * according to the given `Layout`
+     *
+     * With `Layout::DirectoryIsLanguage` the language key is taken from the name of each file's
+     * directory, and the contents of the file are placed under a top-level key named by its file
+     * stem. For example `l10n/en/menu.yml` containing `home: Home` can be looked up as
+     * `menu.home` in the `en` translations.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob_with_layout("fixtures/layout/**/*.yml", Layout::DirectoryIsLanguage)
+     *     .expect("Failed to load translations");
+     * assert_eq!(Some("Startseite"), y10n.lookup("menu.home", &["de".into()]));
+     * ```
+     */
+    pub fn from_glob_with_layout(pattern: &str, layout: Layout) -> Result<Self, Error> {
+        Self::builder().glob(pattern).layout(layout).build()
+    }
+
+    /**
+     * Create and load a Y10n instance from the yml files in the given glob, deriving the language
+     * key of each file from its path with the given closure rather than from its file stem
+     *
+     * Files which the closure returns `None` for are skipped. The derived keys are normalized
+     * just like file stems, so `EN-us` becomes `en-US`.
+     *
+     * ```rust
+     * use y10n::*;
+     * // Files named like `strings.en.yml`
+     * let y10n = Y10n::from_glob_with_key("fixtures/keyed/**/*.yml", |path| {
+     *     let name = path.file_name()?.to_str()?;
+     *     let segments: Vec<&str> = name.split('.').collect();
+     *     segments.len().checked_sub(2).map(|i| segments[i].to_string())
+     * })
+     * .expect("Failed to load translations");
+     * assert_eq!(Some("moin"), y10n.lookup("greeting", &["de".into()]));
+     * ```
+     */
+    pub fn from_glob_with_key<F>(pattern: &str, key: F) -> Result<Self, Error>
+    where
+        F: Fn(&Path) -> Option<String> + Send + Sync + 'static,
+    {
+        Self::builder().glob(pattern).key_with(key).build()
+    }
+
+    /**
+     * Create and load a Y10n instance from the yml files in each of the given globs
+     *
+     * Files from different patterns which share a file stem, such as `l10n/common/en.yml` and
+     * `l10n/pages/en.yml`, are merged into a single language rather than replacing each other.
+     * The patterns are loaded in order, so when both files define the same key the value from the
+     * later pattern wins, while nested mappings are merged key by key.
+     *
+     * ```rust
+     * use y10n::*;
+     * let patterns = ["fixtures/globs/common/**/*.yml", "fixtures/globs/pages/**/*.yml"];
+     * let y10n = Y10n::from_globs(&patterns)
+     *     .expect("Failed to load translations");
+     * assert_eq!(Some("Home"), y10n.lookup("nav.home", &["en".into()]));
+     * assert_eq!(Some("Welcome home"), y10n.lookup("home.title", &["en".into()]));
+     * ```
+     */
+    pub fn from_globs(patterns: &[&str]) -> Result<Self, Error> {
+        patterns
+            .iter()
+            .fold(Self::builder(), |builder, pattern| builder.glob(pattern))
+            .build()
+    }
+
+    /**
+     * Create and load a Y10n instance from the yml files in the given glob, skipping any files
+     * which fail to load rather than failing entirely
+     *
+     * The instance is returned along with the path and error of every file which was skipped, so
+     * that a single malformed file only takes its own language down. An invalid pattern is
+     * reported with the pattern as its path.
+     *
+     * ```rust
+     * use y10n::*;
+     * let (y10n, failures) = Y10n::from_glob_lenient("fixtures/broken/**/*.yml");
+     * assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+     * for (path, error) in failures.iter() {
+     *     eprintln!("Skipped {}: {}", path.display(), error);
+     * }
+     * ```
+     */
+    pub fn from_glob_lenient(pattern: &str) -> (Self, Vec<(PathBuf, Error)>) {
+        Self::builder().glob(pattern).build_lenient()
+    }
+
+    /**
+     * Create and load a Y10n instance from the yml files in the glob, failing with
+     * `Error::NoTranslationsFound` when no translations were loaded
+     *
+     * `from_glob` happily returns an empty instance for a pattern which matches nothing, such as
+     * a misconfigured directory, which otherwise only shows up later as empty localizations.
+     *
+     * ```rust
+     * use y10n::*;
+     * assert!(Y10n::from_glob_required("l10n/**/*.yml").is_ok());
+     * assert!(matches!(
+     *     Y10n::from_glob_required("missing/**/*.yml"),
+     *     Err(Error::NoTranslationsFound)
+     * ));
+     * ```
+     */
+    pub fn from_glob_required(pattern: &str) -> Result<Self, Error> {
+        let y10n = Self::from_glob(pattern)?;
+        if y10n.is_empty() {
+            return Err(Error::NoTranslationsFound);
+        }
+        Ok(y10n)
+    }
+
+    /**
+     * Load the translation files in the glob into this instance, merging files which resolve to
+     * an already loaded language key into it
+     *
+     * When `failures` are given, files which fail to load are recorded there and skipped,
+     * otherwise the first failure is returned.
+     */
+    fn load_glob(
+        &mut self,
+        pattern: &str,
+        layout: Layout,
+        options: &LoadOptions,
+        key_fn: Option<&KeyFn>,
+        mut failures: Option<&mut Vec<(PathBuf, Error)>>,
+    ) -> Result<(), Error> {
         trace!(
             "Attempting to load translations from glob pattern: {:?}",
             pattern
         );
 
-        for entry in glob(pattern).expect("Failed to read glob pattern") {
-            match entry {
-                Ok(path) => {
-                    trace!("Loading translations from: {}", path.display());
+        let entries = glob(pattern).map_err(|source| Error::Pattern {
+            pattern: pattern.to_string(),
+            source,
+        })?;
 
-                    if let Some(stem) = path.file_stem() {
-                        let key = stem.to_string_lossy();
-                        // TODO: Make this error handling more robust
-                        let value = serde_yaml::from_reader(
-                            File::open(&path).expect("Failed to load file"),
-                        )
-                        .expect("Failed to deserialize YAML");
+        let mut matched = false;
+        for entry in entries {
+            matched = true;
+            let path = match entry {
+                Ok(path) => path,
+                Err(e) => {
+                    warn!("{:?}", e);
+                    continue;
+                }
+            };
+            let stem = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => continue,
+            };
+            let language = match (key_fn, layout) {
+                (Some(key_fn), _) => key_fn(&path),
+                (None, Layout::FileIsLanguage) => Some(stem.clone()),
+                (None, Layout::DirectoryIsLanguage) => path
+                    .parent()
+                    .and_then(|dir| dir.file_name())
+                    .map(|language| language.to_string_lossy().to_string()),
+            };
+            let key = match language {
+                Some(language) => normalize_key(&language),
+                None => {
+                    debug!(
+                        "Skipping translations without a language: {}",
+                        path.display()
+                    );
+                    continue;
+                }
+            };
 
-                        this.translations.insert(key.to_string(), value);
+            trace!("Loading translations from: {}", path.display());
+            let value = match parse_file(&path, options) {
+                Ok(value) => value,
+                Err(e) => {
+                    error!("Failed to load {}: {}", path.display(), e);
+                    match failures.as_mut() {
+                        Some(failures) => {
+                            failures.push((path, e));
+                            continue;
+                        }
+                        None => return Err(e),
                     }
                 }
-                Err(e) => warn!("{:?}", e),
+            };
+
+            let value = match layout {
+                Layout::FileIsLanguage => value,
+                Layout::DirectoryIsLanguage => {
+                    let mut namespaced = serde_yaml::Mapping::new();
+                    namespaced.insert(stem.into(), value);
+                    serde_yaml::Value::Mapping(namespaced)
+                }
+            };
+
+            self.insert_language(key, value);
+        }
+        if !matched {
+            warn!(
+                "No translation files matched the glob pattern: {:?}",
+                pattern
+            );
+        }
+        self.cache.clear();
+        Ok(())
+    }
+
+    /**
+     * Insert the translations of a language, merging them into the language's already loaded
+     * translations if there are any
+     */
+    fn insert_language(&mut self, key: String, value: serde_yaml::Value) {
+        match self.translations.get_mut(&key) {
+            Some(existing) => merge_yaml(existing, value, self.max_depth, self.merge_strategy),
+            None => {
+                self.translations.insert(key, value);
             }
         }
+    }
+
+    /**
+     * Create an empty instance with the same merge settings, for loading translations which will
+     * later be moved into this one
+     */
+    #[cfg(feature = "watch")]
+    fn loader(&self) -> Self {
+        let mut loader = Self::new();
+        loader.max_depth = self.max_depth;
+        loader.merge_strategy = self.merge_strategy;
+        loader
+    }
+
+    /**
+     * Load the translations of a single language from every file in the glob whose stem resolves
+     * to its key, merged in the same way as `from_glob`, e.g. both `common/en.yml` and
+     * `pages/en.yml` for `en` with `l10n/**/*.yml`
+     *
+     * Returns `None` when no file in the glob resolves to the key.
+     */
+    #[cfg(feature = "watch")]
+    fn load_language(
+        mut self,
+        pattern: &str,
+        key: &str,
+        options: &LoadOptions,
+    ) -> Result<Option<serde_yaml::Value>, Error> {
+        let wanted = key.to_string();
+        let key_fn = move |path: &Path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .filter(|stem| normalize_key(stem) == wanted)
+        };
+        self.load_glob(
+            pattern,
+            Layout::FileIsLanguage,
+            options,
+            Some(&key_fn),
+            None,
+        )?;
+        Ok(self.translations.remove(key))
+    }
+
+    /**
+     * Create and load a Y10n instance from in-memory sources
+     *
+     * Each source is a pair of the language key, such as `en`, and the raw bytes of the
+     * translations which are parsed according to the given `Format`. This is useful where there is
+     * no filesystem to load translations from, such as in WebAssembly.
+     *
+     * Language keys are normalized and sources which share a key are merged, just like the files
+     * loaded by `from_glob`, so `("en-us", ..)` is loaded as `en-US`.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_sources(&[("en", b"greeting: hello")], Format::Yaml)
+     *     .expect("Failed to load translations");
+     * assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+     * ```
+     */
+    pub fn from_sources(sources: &[(&str, &[u8])], format: Format) -> Result<Self, Error> {
+        let mut this = Self::new();
+        let options = LoadOptions::default();
+
+        for (key, bytes) in sources {
+            trace!("Loading translations for {} from memory", key);
+            let value = match format {
+                Format::Yaml => parse_yaml(*bytes, &options)?,
+                #[cfg(feature = "toml")]
+                Format::Toml => parse_toml(*bytes, &options)?,
+                #[cfg(feature = "gettext")]
+                Format::Gettext => parse_gettext(*bytes, &options)?,
+            };
+            this.insert_language(normalize_key(key), value);
+        }
+        Ok(this)
+    }
+
+    /**
+     * Create and load a Y10n instance from pairs of language keys and YAML contents, such as the
+     * output of `include_str!`, which embeds the translations in the binary
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_pairs(vec![
+     *     ("en", include_str!("../l10n/en.yml")),
+     *     ("de", include_str!("../l10n/de.yml")),
+     * ])
+     * .expect("Failed to load translations");
+     * assert_eq!(Some("moin moin"), y10n.lookup("greeting", &["de".into()]));
+     * ```
+     *
+     * This is a shortcut for `from_sources` with `Format::Yaml`.
+     */
+    pub fn from_pairs<I, K, S>(pairs: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (K, S)>,
+        K: Into<String>,
+        S: AsRef<str>,
+    {
+        let pairs: Vec<(String, S)> = pairs.into_iter().map(|(k, s)| (k.into(), s)).collect();
+        let sources: Vec<(&str, &[u8])> = pairs
+            .iter()
+            .map(|(key, source)| (key.as_str(), source.as_ref().as_bytes()))
+            .collect();
+        Self::from_sources(&sources, Format::Yaml)
+    }
+
+    /**
+     * Create and load a Y10n instance with a single language from a reader of YAML, such as a
+     * response body fetched from a database or object store
+     */
+    pub fn from_reader<R: std::io::Read>(key: &str, reader: R) -> Result<Self, Error> {
+        let mut this = Self::new();
+        this.add_language_from_reader(key, reader)?;
+        Ok(this)
+    }
+
+    /**
+     * Create and load a Y10n instance with a single language from a string of YAML
+     *
+     * ```rust
+     * use y10n::*;
+     * let mut y10n = Y10n::from_str("en", "greeting: hello").expect("Failed to load translations");
+     * y10n.add_language_from_str("de", "greeting: moin").expect("Failed to load translations");
+     * assert_eq!(Some("moin"), y10n.lookup("greeting", &["de".into()]));
+     * ```
+     */
+    pub fn from_str(key: &str, yaml: &str) -> Result<Self, Error> {
+        Self::from_reader(key, yaml.as_bytes())
+    }
+
+    /**
+     * Add (or replace) the translations of a language from a string of YAML
+     *
+     * The translations are parsed and checked just like those loaded by `from_glob`, and the
+     * previously loaded translations for the language are kept if they fail to load.
+     */
+    pub fn add_language_from_str(&mut self, key: &str, yaml: &str) -> Result<(), Error> {
+        self.add_language_from_reader(key, yaml.as_bytes())
+    }
+
+    /**
+     * Add (or replace) the translations of a language from a reader of YAML, see
+     * `add_language_from_str`
+     */
+    pub fn add_language_from_reader<R: std::io::Read>(
+        &mut self,
+        key: &str,
+        reader: R,
+    ) -> Result<(), Error> {
+        trace!("Loading translations for {} from a reader", key);
+        let value = parse_yaml(reader, &LoadOptions::default())?;
+        self.replace_language(key, value);
+        Ok(())
+    }
+
+    /**
+     * Reload the translations of a single language from a file, such as after a deploy, leaving
+     * the other languages untouched
+     *
+     * The file is parsed according to its extension just like the files loaded by `from_glob`, and
+     * the key is normalized in the same way, so reloading `en-us` replaces the loaded `en-US`. If
+     * it fails to load, the previously loaded translations of the language are kept and the error
+     * is returned.
+     *
+     * ```rust,no_run
+     * use std::path::Path;
+     * use y10n::*;
+     * let mut y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * y10n.reload_language("de", Path::new("l10n/de.yml")).expect("Failed to reload");
+     * ```
+     */
+    pub fn reload_language(&mut self, key: &str, path: &Path) -> Result<(), Error> {
+        trace!(
+            "Reloading translations for {} from: {}",
+            key,
+            path.display()
+        );
+        let value = parse_file(path, &LoadOptions::default())?;
+        self.replace_language(key, value);
+        Ok(())
+    }
+
+    /**
+     * Insert (or replace) the translations of a language, normalizing the casing of its key like
+     * the file stems loaded by `from_glob`
+     */
+    fn replace_language(&mut self, key: &str, value: serde_yaml::Value) {
+        self.translations.insert(normalize_key(key), value);
+        self.cache.clear();
+    }
+
+    /**
+     * Layer multiple Y10n instances into a single one, with earlier sources taking priority over
+     * later sources
+     *
+     * This is useful for stacking translations, for example `vec![tenant, theme, base]` where the
+     * tenant's strings override the theme's, which in turn override the base strings. Languages are
+     * merged key by key so a higher priority layer only needs to contain the strings it overrides.
+     *
+     * Mappings are merged recursively and scalars from a higher priority layer replace those of
+     * lower priority layers. Sequences present in more than one layer are combined with the
+     * `MergeStrategy` of the first (highest priority) source, replacing them by default.
+     */
+    pub fn layered(sources: Vec<Y10n>) -> Self {
+        let mut this = Self::new();
+        if let Some(first) = sources.first() {
+            this.merge_strategy = first.merge_strategy;
+        }
+        for source in sources.into_iter().rev() {
+            this.merge(source);
+        }
         this
     }
 
+    /**
+     * Replace every loaded translation with the translations of `new` in one step
+     *
+     * Languages which are not present in `new` are removed. Settings such as the missing key
+     * handler and whitespace policy are kept. When a `Y10n` is shared between threads behind an
+     * `Arc<RwLock<Y10n>>`, the new set can be loaded ahead of time and swapped in while holding the
+     * write lock, so readers never observe a partially replaced set:
+     *
+     * ```rust
+     * use std::sync::{Arc, RwLock};
+     * use y10n::*;
+     * let current = Y10n::from_sources(&[("en", b"greeting: hello")], Format::Yaml).unwrap();
+     * let shared = Arc::new(RwLock::new(current));
+     * let new = Y10n::from_sources(&[("en", b"greeting: howdy")], Format::Yaml).unwrap();
+     * shared.write().unwrap().replace_all(new);
+     * ```
+     */
+    pub fn replace_all(&mut self, new: Y10n) {
+        self.translations = new.translations;
+        self.cache.clear();
+    }
+
+    /**
+     * Merge the translations of another Y10n into this one, with `other` winning on conflicts
+     *
+     * Languages present in both are merged key by key, so `other` only needs to contain the
+     * strings it overrides, and languages only present in `other` are added wholesale. This allows
+     * an application to layer its own strings over the defaults shipped with a library:
+     *
+     * ```rust
+     * use y10n::*;
+     * let mut base = Y10n::from_sources(&[("en", b"greeting: hello\nfarewell: bye")], Format::Yaml)
+     *     .expect("Failed to load translations");
+     * let app = Y10n::from_sources(&[("en", b"greeting: howdy")], Format::Yaml)
+     *     .expect("Failed to load translations");
+     * base.merge(app);
+     * assert_eq!(Some("howdy"), base.lookup("greeting", &["en".into()]));
+     * assert_eq!(Some("bye"), base.lookup("farewell", &["en".into()]));
+     * ```
+     *
+     * See `layered` for combining more than two instances at once.
+     */
+    pub fn merge(&mut self, other: Y10n) {
+        self.cache.clear();
+
+        for (key, value) in other.translations {
+            match self.translations.get_mut(&key) {
+                Some(existing) => merge_yaml(existing, value, self.max_depth, self.merge_strategy),
+                None => {
+                    self.translations.insert(key, value);
+                }
+            }
+        }
+
+        if other.missing_key_handler.is_some() {
+            self.missing_key_handler = other.missing_key_handler;
+        }
+        self.globals.extend(other.globals);
+    }
+
     /**
      * Return a Vec of all the names of languages that have been loaded
      * These are conventionally just the file stems of the yml files loaded
@@ -79,6 +761,31 @@ impl Y10n {
         self.translations.keys().collect()
     }
 
+    /**
+     * Returns true when no languages have been loaded
+     */
+    pub fn is_empty(&self) -> bool {
+        self.translations.is_empty()
+    }
+
+    /**
+     * Return the regions which have been loaded for the given language code
+     *
+     * For example with `en.yml`, `en-US.yml`, and `en-GB.yml` loaded, the regions for `en` would be
+     * `["GB", "US"]`.
+     */
+    pub fn regions_for(&self, code: &str) -> Vec<String> {
+        let mut regions: Vec<String> = self
+            .translations
+            .keys()
+            .filter_map(|key| Language::from(key).ok())
+            .filter(|lang| lang.code == code)
+            .filter_map(|lang| lang.region)
+            .collect();
+        regions.sort();
+        regions
+    }
+
     /**
      * Returns the merged serde_yaml::Value for the given sets of languages.
      *
@@ -86,14 +793,20 @@ impl Y10n {
      * FOr example if the German `de` translation file only has one string in it, but the English
      * `en` file has 10, then this function could be called with a Vec of `Language` instances of
      * `[de, en]` and the result would contain the one German string and 9 English strings.
+     *
+     * A language with a region consults its regional translations (`fr-CA`) and then the
+     * translations for its base language (`fr`) before moving on to the next language.
+     *
+     * If the translations declare a default language with a `_config` key, that language is always
+     * merged in as the final fallback.
      */
     pub fn localize(&self, languages: &[Language]) -> serde_yaml::Value {
         use serde_yaml::{Mapping, Value};
 
         let mut values = vec![];
 
-        for lang in languages {
-            if let Some(value) = self.translations.get(&lang.code) {
+        for key in self.chain(languages) {
+            if let Some(value) = self.translations.get(&key) {
                 values.push(value.clone());
             }
         }
@@ -101,185 +814,4467 @@ impl Y10n {
         let mut map = Value::Mapping(Mapping::new());
 
         for value in values.into_iter().rev() {
-            merge_yaml(&mut map, value);
+            merge_yaml(&mut map, value, self.max_depth, self.merge_strategy);
+        }
+
+        if let Some(map) = map.as_mapping_mut() {
+            map.remove(&Value::String(CONFIG_KEY.to_string()));
+            map.remove(&Value::String(FORMAT_KEY.to_string()));
         }
         map
     }
 
     /**
-     * Lookup a specific token from the language files using the specified language codes
+     * Returns the merged translations for the given languages just like `localize`, converted to
+     * JSON for handing to client-side code such as a single page application
+     *
+     * Strings, numbers, booleans, sequences, and mappings are converted to their JSON equivalents.
+     * Mapping keys which aren't strings, such as `1:` or `yes:`, become their string form.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * let json = y10n.localize_json(&["de".into(), "en".into()]);
+     * assert_eq!(json["greeting"], "moin moin");
+     * let script = format!("window.I18N = {};", json);
+     * ```
+     *
+     * When inlining the JSON into a `<script>` element, remember that translations containing
+     * `</script>` need escaping just like any other user-controlled content.
      */
-    pub fn lookup(&self, token: &str, languages: &[Language]) -> Option<&str> {
-        for lang in languages {
-            if let Some(value) = self.translations.get(&lang.code) {
-                if let Some(values) = value.as_mapping() {
-                    if let Some(translation) =
-                        values.get(&serde_yaml::Value::String(token.to_string()))
-                    {
-                        return translation.as_str();
-                    }
+    pub fn localize_json(&self, languages: &[Language]) -> serde_json::Value {
+        to_json(&self.localize(languages))
+    }
+
+    /**
+     * Returns the merged serde_yaml::Value for the given languages just like `localize`, failing
+     * with `Error::MissingRegion` in strict region mode when a requested region isn't loaded
+     *
+     * Outside of strict region mode this always succeeds.
+     */
+    pub fn try_localize(&self, languages: &[Language]) -> Result<serde_yaml::Value, Error> {
+        if self.strict_regions {
+            let ambient;
+            let requested = if languages.is_empty() {
+                ambient = current_languages();
+                &ambient[..]
+            } else {
+                languages
+            };
+
+            for lang in requested.iter().filter(|lang| lang.region.is_some()) {
+                if !self.translations.contains_key(&lang.key()) {
+                    return Err(Error::MissingRegion(lang.key()));
                 }
             }
         }
-        None
+        Ok(self.localize(languages))
     }
-}
 
-/**
- * Parse a string containing the value of an Accept-Language header
- *
- * For example: 'en,de;q=0.5`
- */
-pub fn parse_accept_language(header: &str) -> Vec<Language> {
-    trace!("Parsing languages from: {}", header);
-    let mut results = vec![];
+    /**
+     * Set whether `try_localize` requires the translations of every requested region to be
+     * loaded, rather than falling back to the base language, e.g. `en-US.yml` must exist when
+     * `en-US` is requested instead of only using `en.yml`
+     *
+     * This is useful for content which must exist for every region, such as legal text.
+     */
+    pub fn set_strict_regions(&mut self, enabled: bool) {
+        self.strict_regions = enabled;
+    }
 
-    for part in header.split(",") {
-        if let Ok(language) = Language::from(part) {
-            results.push(language);
-        }
+    /**
+     * Set how sequences present in more than one language are combined by `localize`, which
+     * defaults to `MergeStrategy::ReplaceSequences`
+     *
+     * Mappings are always merged recursively and scalars of the preferred language always replace
+     * those of the fallbacks.
+     */
+    pub fn set_merge_strategy(&mut self, strategy: MergeStrategy) {
+        self.merge_strategy = strategy;
+        self.cache.clear();
     }
-    results
-}
 
-/**
- * Locale ontains useful information about a locale
- */
-#[derive(Clone, Debug)]
-pub struct Language {
-    /// A non-region based language code, i.e. "en" or "de"
-    pub code: String,
-    region: Option<String>,
-    quality: f64,
-}
+    /**
+     * Set the maximum nesting of mappings which is merged by `localize` and traversed when listing
+     * dotted keys, which defaults to `DEFAULT_MAX_DEPTH`
+     *
+     * This guards against overflowing the stack with deeply nested translations. Mappings nested
+     * deeper than the limit are not merged, the value of the preferred language replaces the
+     * fallback's entirely, and their keys are skipped by methods such as `all_placeholders`. A
+     * warning is logged whenever the limit is reached.
+     *
+     * Files nested deeper than 128 levels are already rejected with `Error::Yaml` when loading.
+     */
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+        self.cache.clear();
+    }
 
-impl Language {
     /**
-     * Create a `Language` instance from a segment of an `Accepts-Language` header
+     * Returns the merged serde_yaml::Value for the given languages just like `localize`, reusing
+     * the result of previous calls with the same languages
      *
-     * For example `en` or `de;q=0.5`.
+     * The most recently used results are kept, up to the capacity set with `set_cache_capacity`,
+     * which bounds memory use for servers facing many different combinations of languages. The
+     * cache is cleared whenever translations are reloaded.
      */
-    fn from(segment: &str) -> Result<Language, Error> {
-        if let Some(captures) = LANG_REGEX.captures(segment) {
-            Ok(Language {
-                code: captures
-                    .name("code")
-                    .map_or("unknown".to_string(), |c| c.as_str().to_string()),
-                region: captures
-                    .name("region")
-                    .map_or(None, |c| Some(c.as_str().to_string())),
-                quality: captures
-                    .name("quality")
-                    .map_or(1.0, |c| c.as_str().parse().unwrap_or(0.0)),
+    pub fn localize_cached(&self, languages: &[Language]) -> Arc<serde_yaml::Value> {
+        let chain = self.chain(languages);
+        if let Some(value) = self.cache.get(&chain) {
+            return value;
+        }
+
+        let value = Arc::new(self.localize(languages));
+        self.cache.insert(chain, value.clone());
+        value
+    }
+
+    /**
+     * Discard every merged result kept by `localize_cached`
+     *
+     * The cache is already cleared whenever translations are loaded or settings which affect the
+     * merged results are changed, so this is only needed to release the memory of the cached
+     * results, e.g. after a burst of requests for unusual combinations of languages.
+     */
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /**
+     * Set the maximum number of merged results kept by `localize_cached`, evicting the least
+     * recently used results beyond it
+     *
+     * The default capacity is 64, and a capacity of zero disables caching.
+     */
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache.set_capacity(capacity);
+    }
+
+    /**
+     * Return the translation keys to consult, in order, for the given languages
+     *
+     * Each language is expanded into its own fallback chain before moving on to the next, so
+     * `[fr-CA, de-AT]` consults `fr-CA`, `fr`, `de-AT`, and then `de`. The wildcard language `*`
+     * expands to the default language, see `set_default_language`. The default language is always
+     * consulted last.
+     */
+    fn chain(&self, languages: &[Language]) -> Vec<String> {
+        let mut chain: Vec<String> = vec![];
+        let ambient;
+        let languages = if languages.is_empty() {
+            ambient = current_languages();
+            &ambient[..]
+        } else {
+            languages
+        };
+
+        for lang in languages {
+            for key in self.expand(lang) {
+                if !chain.contains(&key) {
+                    chain.push(key);
+                }
+            }
+        }
+
+        if let Some(default) = self.default_language() {
+            if !chain.contains(&default) {
+                chain.push(default);
+            }
+        }
+
+        for key in self.fallback_only.iter() {
+            if !chain.contains(key) {
+                chain.push(key.clone());
+            }
+        }
+        chain
+    }
+
+    /**
+     * Return the translation keys to consult for a single language, expanding the wildcard `*`
+     * into the default language, or the first loaded language by key when there is no default
+     */
+    fn expand(&self, lang: &Language) -> Vec<String> {
+        if !lang.is_wildcard() {
+            return lang.fallback_keys();
+        }
+
+        self.default_language()
+            .or_else(|| {
+                let mut keys: Vec<&String> = self
+                    .translations
+                    .keys()
+                    .filter(|key| !self.fallback_only.contains(key))
+                    .collect();
+                keys.sort();
+                keys.first().map(|key| key.to_string())
             })
+            .into_iter()
+            .collect()
+    }
+
+    /**
+     * Set the default language, which is consulted after all of the requested languages and which
+     * the wildcard `*` of an `Accept-Language` header resolves to
+     *
+     * This takes precedence over a default declared by the translations with a `_config` key.
+     *
+     * ```rust
+     * use y10n::*;
+     * let mut y10n = Y10n::from_pairs(vec![("de", "greeting: moin"), ("en", "greeting: hello")])
+     *     .expect("Failed to load translations");
+     * y10n.set_default_language("en");
+     * assert_eq!(Some("hello"), y10n.lookup("greeting", &parse_accept_language("*")));
+     * ```
+     */
+    pub fn set_default_language(&mut self, key: &str) {
+        self.default_language = Some(normalize_key(key));
+        self.cache.clear();
+    }
+
+    /**
+     * Return the default language set with `set_default_language`, or else the one declared by
+     * the translations
+     */
+    fn default_language(&self) -> Option<String> {
+        self.default_language
+            .clone()
+            .or_else(|| self.configured_default())
+    }
+
+    /**
+     * Mark a loaded language as only being used as a fallback
+     *
+     * A fallback-only language, such as a generic `en`, is never offered to users by `negotiate`
+     * or `best_match`, but is always consulted last by `localize` and `lookup` to fill in any
+     * strings missing from the requested languages.
+     */
+    pub fn set_fallback_only(&mut self, key: &str) {
+        let key = normalize_key(key);
+        if !self.fallback_only.contains(&key) {
+            self.fallback_only.push(key);
+            self.cache.clear();
+        }
+    }
+
+    /**
+     * Return the default language declared by the translations themselves with a `_config` key:
+     *
+     * ```yaml
+     * _config:
+     *   default: en
+     * ```
+     *
+     * If more than one language declares a default, the first by language key wins.
+     */
+    fn configured_default(&self) -> Option<String> {
+        let mut keys: Vec<&String> = self.translations.keys().collect();
+        keys.sort();
+
+        keys.into_iter()
+            .filter_map(|key| self.translations[key].get(CONFIG_KEY))
+            .filter_map(|config| config.get("default"))
+            .find_map(|default| default.as_str())
+            .map(normalize_key)
+    }
+
+    /**
+     * Check that every plural mapping provides all of the plural categories required by the rules
+     * of its language, returning a `PluralGap` for each one which doesn't
+     *
+     * For example a Russian plural must provide `one`, `few`, `many` and `other` branches, whereas
+     * an English plural only needs `one` and `other`. Without the required branches counts will
+     * silently fall back to the `other` branch.
+     */
+    pub fn validate_plurals(&self) -> Vec<PluralGap> {
+        let mut gaps = vec![];
+
+        for (language, value) in self.translations.iter() {
+            let code = Language::from(language).map_or(language.clone(), |lang| lang.code);
+
+            visit(
+                value,
+                "",
+                self.max_depth,
+                &mut |key, value| match value.as_mapping() {
+                    Some(branches) if plural::is_plural(branches) => {
+                        let missing = plural::missing_categories(branches, &code);
+                        if !missing.is_empty() {
+                            gaps.push(PluralGap {
+                                language: language.clone(),
+                                key: key.to_string(),
+                                missing,
+                            });
+                        }
+                        false
+                    }
+                    _ => true,
+                },
+            );
+        }
+        gaps.sort_by(|a, b| (&a.language, &a.key).cmp(&(&b.language, &b.key)));
+        gaps
+    }
+
+    /**
+     * Run all of the validations of the translations, collecting every problem found into a
+     * single report, which makes validating translations at startup a one-liner
+     *
+     * The `reference` language, which is usually the language the strings are written in, must be
+     * loaded. The validations run are `missing_keys`, `validate_plurals`,
+     * `validate_branch_placeholders` and `validate_placeholders`.
+     */
+    pub fn verify(&self, reference: &str) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+
+        if !self.translations.contains_key(reference) {
+            errors.push(ValidationError::MissingReference(reference.to_string()));
+        }
+        let mut missing: Vec<(String, Vec<String>)> =
+            self.missing_keys(reference).into_iter().collect();
+        missing.sort();
+        errors.extend(
+            missing
+                .into_iter()
+                .map(|(language, keys)| ValidationError::MissingKeys { language, keys }),
+        );
+        errors.extend(
+            self.validate_plurals()
+                .into_iter()
+                .map(ValidationError::PluralGap),
+        );
+        errors.extend(
+            self.validate_branch_placeholders()
+                .into_iter()
+                .map(ValidationError::BranchMismatch),
+        );
+        errors.extend(
+            self.validate_placeholders(reference)
+                .into_iter()
+                .map(ValidationError::PlaceholderMismatch),
+        );
+
+        if errors.is_empty() {
+            Ok(())
         } else {
-            Err(Error::Generic)
+            Err(errors)
+        }
+    }
+
+    /**
+     * Return the dotted keys present in the `base` language which each of the other languages
+     * lack, which is useful for failing CI when strings haven't been translated yet
+     *
+     * Only languages with missing keys are included, so an empty map means that every language is
+     * complete. Plural mappings are treated as a single key, see `validate_plurals` for checking
+     * their branches. A regional language such as `en-US` counts the keys of its base language
+     * `en` as present, since they're used as its fallback.
+     */
+    pub fn missing_keys(&self, base: &str) -> HashMap<String, Vec<String>> {
+        let base_keys = match self.translations.get(base) {
+            Some(value) => self.leaf_keys(value),
+            None => return HashMap::new(),
+        };
+
+        let mut missing = HashMap::new();
+        for language in self
+            .translations
+            .keys()
+            .filter(|language| *language != base)
+        {
+            let present = self.present_keys(language);
+            let mut keys: Vec<String> = base_keys.difference(&present).cloned().collect();
+            if !keys.is_empty() {
+                keys.sort();
+                missing.insert(language.clone(), keys);
+            }
+        }
+        missing
+    }
+
+    /**
+     * Return the dotted keys available to a language, which for a regional language such as
+     * `en-US` includes the keys of its base language `en`
+     */
+    fn present_keys(&self, language: &str) -> HashSet<String> {
+        let mut present = HashSet::new();
+        let fallback_keys = Language::from(language)
+            .map(|lang| lang.fallback_keys())
+            .unwrap_or_else(|_| vec![language.to_string()]);
+        for key in fallback_keys {
+            if let Some(value) = self.translations.get(&key) {
+                present.extend(self.leaf_keys(value));
+            }
+        }
+        present
+    }
+
+    /**
+     * Report the number of keys of every language and how much of the `base` language each of
+     * them covers, e.g. for an admin dashboard
+     *
+     * Keys are counted in the same way as `missing_keys`, so plural mappings count as a single
+     * key and regional languages are covered by the keys of their base language. The stats
+     * serialize to JSON with serde:
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * let stats = y10n.stats("en");
+     * assert_eq!(stats.languages["en"].coverage, 100.0);
+     * let json = serde_json::to_string(&stats).unwrap();
+     * ```
+     *
+     * When the base language isn't loaded, it has no keys, and every language is fully covered.
+     */
+    pub fn stats(&self, base: &str) -> TranslationStats {
+        let base_keys = self.present_keys(base);
+
+        let languages = self
+            .translations
+            .iter()
+            .map(|(language, value)| {
+                let present = self.present_keys(language);
+                let translated = base_keys.intersection(&present).count();
+                let coverage = if base_keys.is_empty() {
+                    100.0
+                } else {
+                    translated as f64 * 100.0 / base_keys.len() as f64
+                };
+                let stats = LanguageStats {
+                    keys: self.leaf_keys(value).len(),
+                    translated,
+                    coverage,
+                };
+                (language.clone(), stats)
+            })
+            .collect();
+
+        TranslationStats {
+            base: base.to_string(),
+            base_keys: base_keys.len(),
+            languages,
+        }
+    }
+
+    /**
+     * Return the dotted keys which each language has but the `base` language lacks, which are
+     * usually left over from strings which have been removed
+     *
+     * Only languages with extra keys are included.
+     */
+    pub fn extra_keys(&self, base: &str) -> HashMap<String, Vec<String>> {
+        let base_keys = match self.translations.get(base) {
+            Some(value) => self.leaf_keys(value),
+            None => return HashMap::new(),
+        };
+
+        let mut extra = HashMap::new();
+        for (language, value) in self.translations.iter() {
+            if language == base {
+                continue;
+            }
+            let mut keys: Vec<String> = self
+                .leaf_keys(value)
+                .difference(&base_keys)
+                .cloned()
+                .collect();
+            if !keys.is_empty() {
+                keys.sort();
+                extra.insert(language.clone(), keys);
+            }
+        }
+        extra
+    }
+
+    /**
+     * Collect the dotted keys of every string, and of every plural mapping as a whole, skipping
+     * the reserved `_config` and `_format` keys
+     */
+    fn leaf_keys(&self, value: &serde_yaml::Value) -> HashSet<String> {
+        let mut keys = HashSet::new();
+        visit(value, "", self.max_depth, &mut |key, value| {
+            if is_reserved(key) {
+                return false;
+            }
+            match value.as_mapping() {
+                Some(branches) if plural::is_plural(branches) => {
+                    keys.insert(key.to_string());
+                    false
+                }
+                Some(_) => true,
+                None => {
+                    keys.insert(key.to_string());
+                    false
+                }
+            }
+        });
+        keys
+    }
+
+    /**
+     * Check that the branches of every plural or select mapping use the same `{{name}}`
+     * placeholders, returning a `BranchMismatch` for each branch which is missing some of them
+     *
+     * For example an `other` branch of "items" which forgot the `{{count}}` used by its `one`
+     * branch renders without a number. Select mappings are those with an `other` branch where
+     * every branch is a string, such as the form factor branches of the handlebars helper.
+     */
+    pub fn validate_branch_placeholders(&self) -> Vec<BranchMismatch> {
+        let mut mismatches = vec![];
+
+        for (language, value) in self.translations.iter() {
+            visit(value, "", self.max_depth, &mut |key, value| {
+                let branches = match value.as_mapping() {
+                    Some(branches) if plural::is_plural(branches) || is_select(branches) => {
+                        branches
+                    }
+                    _ => return true,
+                };
+
+                let placeholders: Vec<(String, HashSet<String>)> = branches
+                    .iter()
+                    .filter_map(|(branch, template)| {
+                        let branch = match branch.as_str() {
+                            Some(branch) => branch.to_string(),
+                            None => branch.as_u64()?.to_string(),
+                        };
+                        Some((branch, interpolate::placeholders(template.as_str()?)))
+                    })
+                    .collect();
+                let all: HashSet<&String> = placeholders.iter().flat_map(|(_, p)| p).collect();
+
+                for (branch, used) in placeholders.iter() {
+                    let mut missing: Vec<String> = all
+                        .iter()
+                        .filter(|name| !used.contains(**name))
+                        .map(|name| name.to_string())
+                        .collect();
+                    if !missing.is_empty() {
+                        missing.sort();
+                        mismatches.push(BranchMismatch {
+                            language: language.clone(),
+                            key: key.to_string(),
+                            branch: branch.clone(),
+                            missing,
+                        });
+                    }
+                }
+                false
+            });
+        }
+        mismatches.sort_by(|a, b| {
+            (&a.language, &a.key, &a.branch).cmp(&(&b.language, &b.key, &b.branch))
+        });
+        mismatches
+    }
+
+    /**
+     * Check that every string of the `base` language uses the same `{{name}}` placeholders as the
+     * string with the same key in each of the other languages, returning a `PlaceholderMismatch`
+     * for each string which differs
+     *
+     * For example a German `greeting: "Hallo"` which dropped the `{{who}}` of the English
+     * `greeting: "Hello {{who}}"` renders without the name. Nested mappings are compared key by
+     * key, and keys which a language doesn't translate are left to `missing_keys`.
+     */
+    pub fn validate_placeholders(&self, base: &str) -> Vec<PlaceholderMismatch> {
+        let base_strings = match self.translations.get(base) {
+            Some(value) => strings(value, self.max_depth),
+            None => return vec![],
+        };
+
+        let mut mismatches = vec![];
+        for (language, value) in self.translations.iter() {
+            if language == base {
+                continue;
+            }
+
+            for (key, translated) in strings(value, self.max_depth) {
+                let expected = match base_strings.get(&key) {
+                    Some(expected) => interpolate::placeholders(expected),
+                    None => continue,
+                };
+                let used = interpolate::placeholders(translated);
+
+                let mut missing: Vec<String> = expected.difference(&used).cloned().collect();
+                let mut extra: Vec<String> = used.difference(&expected).cloned().collect();
+                if !missing.is_empty() || !extra.is_empty() {
+                    missing.sort();
+                    extra.sort();
+                    mismatches.push(PlaceholderMismatch {
+                        language: language.clone(),
+                        key,
+                        missing,
+                        extra,
+                    });
+                }
+            }
+        }
+        mismatches.sort_by(|a, b| (&a.language, &a.key).cmp(&(&b.language, &b.key)));
+        mismatches
+    }
+
+    /**
+     * Return the `{{name}}` placeholders used by every string, keyed by dotted key
+     *
+     * The placeholders of a key are combined across all loaded languages, which makes this useful
+     * for building a glossary of the variables each string expects.
+     */
+    pub fn all_placeholders(&self) -> HashMap<String, HashSet<String>> {
+        let mut all: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for value in self.translations.values() {
+            for (key, s) in strings(value, self.max_depth) {
+                all.entry(key)
+                    .or_default()
+                    .extend(interpolate::placeholders(s));
+            }
+        }
+        all
+    }
+
+    /**
+     * Iterate over every value in the translations of a single language along with its full dotted
+     * key, such as `("menu.file.open", "Open")`, in the order of the translation file
+     *
+     * Mappings are descended into rather than yielded, so only their leaves are returned, which
+     * includes each branch of plural mappings. Sequences are yielded whole under their own key.
+     * The `_config` and `_format` keys are skipped, and nothing is yielded for a language which
+     * isn't loaded.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * for (key, value) in y10n.entries("en") {
+     *     println!("{} = {:?}", key, value);
+     * }
+     * ```
+     */
+    pub fn entries(&self, key: &str) -> impl Iterator<Item = (String, &serde_yaml::Value)> {
+        let mut entries = vec![];
+        if let Some(value) = self.translations.get(key) {
+            visit(value, "", self.max_depth, &mut |key, value| {
+                if is_reserved(key) {
+                    return false;
+                }
+                if value.is_mapping() {
+                    return true;
+                }
+                entries.push((key.to_string(), value));
+                false
+            });
+        }
+        entries.into_iter()
+    }
+
+    /**
+     * Return the number of strings and the total bytes of those strings for every language
+     *
+     * This is useful for deciding which languages to split out of a bundle or load lazily. Plural
+     * branches are counted as separate strings, and the `_config` key is skipped.
+     */
+    pub fn size_report(&self) -> HashMap<String, (usize, usize)> {
+        self.translations
+            .iter()
+            .map(|(language, value)| {
+                let strings = strings(value, self.max_depth);
+                let bytes = strings.values().map(|s| s.len()).sum();
+                (language.clone(), (strings.len(), bytes))
+            })
+            .collect()
+    }
+
+    /**
+     * Compute the average ratio of the length of the `target` language's strings to the length of
+     * the `reference` language's strings, over the keys which both languages provide
+     *
+     * A ratio of `1.35` means that the target strings are 35% longer on average, which is useful
+     * when deciding how much room a layout needs. Lengths are measured in characters, and `0.0`
+     * is returned when the languages have no strings in common.
+     */
+    pub fn expansion_ratio(&self, reference: &str, target: &str) -> f64 {
+        let (reference, target) = match (
+            self.translations.get(reference),
+            self.translations.get(target),
+        ) {
+            (Some(reference), Some(target)) => (
+                strings(reference, self.max_depth),
+                strings(target, self.max_depth),
+            ),
+            _ => return 0.0,
+        };
+
+        let ratios: Vec<f64> = reference
+            .iter()
+            .filter(|(_, source)| !source.is_empty())
+            .filter_map(|(key, source)| {
+                target.get(key).map(|translated| {
+                    translated.chars().count() as f64 / source.chars().count() as f64
+                })
+            })
+            .collect();
+
+        if ratios.is_empty() {
+            return 0.0;
+        }
+        ratios.iter().sum::<f64>() / ratios.len() as f64
+    }
+
+    /**
+     * Serialize the merged translations for the given languages, as produced by `localize`, into
+     * a YAML document
+     *
+     * Keys are sorted recursively by default so that dumps of equivalent translations are
+     * byte-identical, which keeps diffs clean when dumps are committed. This can be disabled with
+     * `set_sort_keys`.
+     */
+    pub fn dump(&self, languages: &[Language]) -> Result<String, Error> {
+        let mut value = self.localize(languages);
+        if self.sort_keys {
+            sort_keys(&mut value);
+        }
+        Ok(serde_yaml::to_string(&value)?)
+    }
+
+    /**
+     * Translate a specific token and interpolate its `{{name}}` placeholders from a map of
+     * variables, without requiring a templating engine
+     *
+     * Placeholders without a variable in the map are left in the output untouched so that
+     * they're easy to spot. See `render_with` for interpolating nested values.
+     *
+     * ```rust
+     * use std::collections::HashMap;
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").expect("Failed to load translations");
+     * let mut vars = HashMap::new();
+     * vars.insert("team".to_string(), "Ferris".to_string());
+     * assert_eq!(
+     *     Some("Thanks for playing Ferris!".to_string()),
+     *     y10n.render("thankyou", &["en".into()], &vars)
+     * );
+     * ```
+     */
+    pub fn render(
+        &self,
+        token: &str,
+        languages: &[Language],
+        vars: &HashMap<String, String>,
+    ) -> Option<String> {
+        let translation = self.translate(token, languages)?;
+        Some(interpolate::interpolate(&translation, &|name| {
+            vars.get(name).cloned()
+        }))
+    }
+
+    /**
+     * Translate a specific token and interpolate its `{{name}}` placeholders from a structured
+     * context, without requiring a templating engine
+     *
+     * Placeholders may be dotted paths, such as `{{user.name}}`, which are resolved against nested
+     * objects in the context. Strings in the context are inserted as-is, and other JSON values are
+     * inserted in their JSON form. Placeholders which can't be resolved are left in the output
+     * untouched so that they're easy to spot.
+     *
+     * Values are inserted literally and never re-parsed, so dates and numbers which the host
+     * application has already formatted, such as `"1.234,56"`, appear exactly as given.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_sources(&[("en", b"welcome: 'Welcome {{user.name}}'")], Format::Yaml)
+     *     .expect("Failed to load translations");
+     * let context = serde_json::json!({"user": {"name": "Ferris"}});
+     * assert_eq!(
+     *     Some("Welcome Ferris".to_string()),
+     *     y10n.render_with("welcome", &["en".into()], &context)
+     * );
+     * ```
+     */
+    pub fn render_with(
+        &self,
+        token: &str,
+        languages: &[Language],
+        context: &serde_json::Value,
+    ) -> Option<String> {
+        let translation = self.translate(token, languages)?;
+        Some(interpolate::interpolate(&translation, &|name| {
+            interpolate::resolve_json(context, name)
+        }))
+    }
+
+    /**
+     * Set the global variables, such as the product name or support email, which templating
+     * helpers make available to every localization string
+     *
+     * Variables passed to an individual call, such as the hash arguments of the handlebars `t`
+     * helper, take precedence over globals of the same name.
+     *
+     * ```rust
+     * use std::collections::HashMap;
+     * use y10n::*;
+     * let mut y10n = Y10n::from_sources(&[("en", b"help: 'Contact {{support_email}}'")], Format::Yaml)
+     *     .expect("Failed to load translations");
+     * let mut globals = HashMap::new();
+     * globals.insert("support_email".to_string(), "help@example.com".to_string());
+     * y10n.set_globals(globals);
+     * assert_eq!(Some("help@example.com"), y10n.globals().get("support_email").map(|s| s.as_str()));
+     * ```
+     */
+    pub fn set_globals(&mut self, globals: HashMap<String, String>) {
+        self.globals = globals;
+    }
+
+    /**
+     * Return the global variables set with `set_globals`
+     */
+    pub fn globals(&self) -> &HashMap<String, String> {
+        &self.globals
+    }
+
+    /**
+     * Set whether `dump` sorts keys, which is enabled by default
+     */
+    pub fn set_sort_keys(&mut self, enabled: bool) {
+        self.sort_keys = enabled;
+    }
+
+    /**
+     * Lookup a specific token from the language files using the specified language codes
+     *
+     * The languages are consulted in order, along with the fallbacks described in `localize`,
+     * and the string of the first language which has the token is returned without merging
+     * anything. A later language is only consulted when the token is absent from the earlier
+     * ones, so a token which is a mapping rather than a string in the first language which has
+     * it returns `None`. Nothing needs to be merged, which makes this cheaper than `localize` for
+     * looking up individual strings.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_pairs(vec![("de", "greeting: 'moin'"), ("en", "farewell: 'bye'")])
+     *     .expect("Failed to load translations");
+     * let languages: Vec<Language> = vec!["de".into(), "en".into()];
+     * assert_eq!(Some("moin"), y10n.lookup("greeting", &languages));
+     * assert_eq!(Some("bye"), y10n.lookup("farewell", &languages));
+     * assert_eq!(None, y10n.lookup("missing", &languages));
+     * ```
+     *
+     * Dotted tokens such as `menu.file.open` resolve strings nested in mappings:
+     *
+     * ```yaml
+     * menu:
+     *   file:
+     *     open: 'Open'
+     * ```
+     *
+     * A key containing literal dots, e.g. `'menu.file.open': 'Open'`, is checked before descending
+     * into nested mappings, so files written with flat dotted keys keep working.
+     *
+     * When no languages are given, the languages set for the current thread with
+     * `with_languages` are used instead. The same applies to every other method which takes
+     * a list of languages.
+     */
+    pub fn lookup(&self, token: &str, languages: &[Language]) -> Option<&str> {
+        self.lookup_value(token, languages)
+            .and_then(|translation| translation.as_str())
+            .map(|translation| self.apply_whitespace(translation))
+    }
+
+    /**
+     * Lookup the raw value of a token, which may be a number, boolean, sequence, or mapping rather
+     * than a string, with the same fallback semantics as `lookup`
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_str("de", "currency_symbol: '€'\ndecimal_places: 2").unwrap();
+     * let places = y10n.lookup_value("decimal_places", &["de".into()]);
+     * assert_eq!(places, Some(&serde_yaml::Value::from(2)));
+     * ```
+     *
+     * The whitespace policy of `set_whitespace` isn't applied to raw values.
+     */
+    pub fn lookup_value(&self, token: &str, languages: &[Language]) -> Option<&serde_yaml::Value> {
+        self.lookup_entry(token, languages).map(|(_, value)| value)
+    }
+
+    /**
+     * Set how leading and trailing whitespace is treated in strings returned by `lookup` and
+     * `pluralize`, which defaults to `Whitespace::Preserve`
+     */
+    pub fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.whitespace = whitespace;
+    }
+
+    fn apply_whitespace<'a>(&self, translation: &'a str) -> &'a str {
+        match self.whitespace {
+            Whitespace::Preserve => translation,
+            Whitespace::Trim => translation.trim(),
+        }
+    }
+
+    /**
+     * Find the raw value for a token along with the language which provided it
+     */
+    fn lookup_entry(
+        &self,
+        token: &str,
+        languages: &[Language],
+    ) -> Option<(&str, &serde_yaml::Value)> {
+        for key in self.chain(languages) {
+            if let Some((key, value)) = self.translations.get_key_value(&key) {
+                if let Some(translation) = value.as_mapping().and_then(|v| find_path(v, token)) {
+                    return Some((key, translation));
+                }
+            }
+        }
+        None
+    }
+
+    /**
+     * Describe how a token is resolved for the given languages as JSON, which is useful for
+     * debugging endpoints
+     *
+     * Every language which would be consulted is listed in order along with whether its
+     * translations are loaded and whether it provides the token, for example:
+     *
+     * ```json
+     * {
+     *   "key": "greeting",
+     *   "resolved": {"language": "de", "value": "moin moin"},
+     *   "consulted": [
+     *     {"language": "fr", "loaded": false, "found": false},
+     *     {"language": "de", "loaded": true, "found": true, "value": "moin moin"},
+     *     {"language": "en", "loaded": true, "found": true, "value": "hello world"}
+     *   ]
+     * }
+     * ```
+     */
+    pub fn explain_json(&self, token: &str, languages: &[Language]) -> serde_json::Value {
+        use serde_json::json;
+
+        let mut resolved = serde_json::Value::Null;
+        let mut consulted = vec![];
+
+        for language in self.chain(languages) {
+            let loaded = self.translations.get(&language);
+            let value = loaded.and_then(|value| find_path(value.as_mapping()?, token));
+
+            match value {
+                Some(value) => {
+                    let value = serde_json::to_value(value).unwrap_or_default();
+                    if resolved.is_null() {
+                        resolved = json!({"language": language, "value": value});
+                    }
+                    consulted.push(json!({
+                        "language": language,
+                        "loaded": true,
+                        "found": true,
+                        "value": value,
+                    }));
+                }
+                None => consulted.push(json!({
+                    "language": language,
+                    "loaded": loaded.is_some(),
+                    "found": false,
+                })),
+            }
+        }
+
+        json!({
+            "key": token,
+            "resolved": resolved,
+            "consulted": consulted,
+        })
+    }
+
+    /**
+     * Lookup the plural form of a token appropriate for the given count
+     *
+     * The token should refer to a mapping of plural branches, for example:
+     *
+     * ```yaml
+     * items:
+     *   zero: 'No items'
+     *   one: 'One item'
+     *   other: 'Many items'
+     * ```
+     *
+     * Branches are chosen with the CLDR plural rules of the language which provided the token,
+     * falling back to the `other` branch. Explicit counts (`"0"`), ranges (`"2-5"`) and open ended
+     * ranges (`"6+"`) may also be used as keys and take precedence over the plural categories.
+     *
+     * The token may also refer to a string with ICU plural arguments, in which `#` is replaced
+     * with the count (see `plural::format_icu`):
+     *
+     * ```yaml
+     * items: '{count, plural, =0 {No items} one {# item} other {# items}}'
+     * ```
+     *
+     * Rules for additional languages can be added with `set_plural_rule`.
+     */
+    pub fn pluralize(
+        &self,
+        token: &str,
+        count: u64,
+        languages: &[Language],
+    ) -> Option<Cow<'_, str>> {
+        let (key, value) = self.lookup_entry(token, languages)?;
+        let code = key.split('-').next().unwrap_or(key);
+        let rule = match self.custom_plural_rules.get(code) {
+            Some(rule) => *rule,
+            None => plural::rule_for(code),
+        };
+
+        match value.as_str() {
+            Some(message) if plural::is_icu(message) => plural::format_icu(message, rule, count)
+                .map(|formatted| Cow::Owned(self.apply_whitespace(&formatted).to_string())),
+            _ => plural::select_with_rule(value.as_mapping()?, rule, count)
+                .and_then(|v| v.as_str())
+                .map(|translation| Cow::Borrowed(self.apply_whitespace(translation))),
+        }
+    }
+
+    /**
+     * Translate a specific token whose string has ICU select arguments, choosing the branch of
+     * each argument from the keyword arguments by name
+     *
+     * ```rust
+     * use std::collections::HashMap;
+     * use y10n::*;
+     * let y10n = Y10n::from_sources(
+     *     &[("en", b"replied: '{gender, select, male {He} female {She} other {They}} replied'")],
+     *     Format::Yaml,
+     * )
+     * .expect("Failed to load translations");
+     * let mut args = HashMap::new();
+     * args.insert("gender".to_string(), "female".to_string());
+     * assert_eq!(
+     *     Some("She replied".to_string()),
+     *     y10n.select("replied", &["en".into()], &args)
+     * );
+     * ```
+     *
+     * Arguments without a keyword argument, or whose value has no branch, use the `other` branch
+     * (see `plural::format_icu_select`). Strings without select arguments are returned as-is.
+     */
+    pub fn select(
+        &self,
+        token: &str,
+        languages: &[Language],
+        args: &HashMap<String, String>,
+    ) -> Option<String> {
+        let translation = self.translate(token, languages)?;
+        plural::format_icu_select(&translation, |name| args.get(name).cloned())
+    }
+
+    /**
+     * Use the given plural rule for the language code, e.g. `eo`, in place of the built-in rules
+     * of `plural::rule_for`
+     */
+    pub fn set_plural_rule(&mut self, code: &str, rule: plural::PluralRule) {
+        self.custom_plural_rules.insert(normalize_key(code), rule);
+    }
+
+    /**
+     * Translate a specific token, consulting the missing key handler when none of the languages
+     * have it
+     *
+     * Without a handler registered with `set_missing_key_handler` this behaves just like `lookup`,
+     * unless `set_humanize_missing_keys` has been enabled.
+     */
+    pub fn translate(&self, token: &str, languages: &[Language]) -> Option<Cow<'_, str>> {
+        match self.lookup(token, languages) {
+            Some(translation) => Some(Cow::Borrowed(translation)),
+            None => {
+                debug!("No translation found for: {}", token);
+                self.missing_key_handler
+                    .as_ref()
+                    .and_then(|handler| (handler.0)(token))
+                    .or_else(|| self.humanize_missing_keys.then(|| humanize_key(token)))
+                    .map(Cow::Owned)
+            }
+        }
+    }
+
+    /**
+     * Enable rendering missing keys in a human readable form with `humanize_key`, so that
+     * `user_profile.edit_button` is translated as "Edit Button"
+     *
+     * This is intended for development and prototyping, where user interfaces may be built before
+     * any translations exist, and should not be enabled in production.
+     */
+    pub fn set_humanize_missing_keys(&mut self, enabled: bool) {
+        self.humanize_missing_keys = enabled;
+    }
+
+    /**
+     * Register a closure to be invoked by `translate` with any key which none of the requested
+     * languages provide
+     *
+     * The closure can produce a dynamic fallback for the key, or return `None` to leave it
+     * untranslated.
+     */
+    pub fn set_missing_key_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.missing_key_handler = Some(MissingKeyHandler(Arc::new(handler)));
+    }
+
+    /**
+     * Lookup a specific token, returning the given `default` if none of the languages have it
+     */
+    pub fn lookup_or<'a>(
+        &'a self,
+        token: &str,
+        languages: &[Language],
+        default: &'a str,
+    ) -> &'a str {
+        self.lookup(token, languages).unwrap_or(default)
+    }
+
+    /**
+     * Lookup a boolean value, such as a feature flag which differs between regions
+     *
+     * `None` is returned when none of the languages have the token or when its value isn't a
+     * boolean, strings such as `"true"` are not coerced.
+     */
+    pub fn lookup_bool(&self, token: &str, languages: &[Language]) -> Option<bool> {
+        self.lookup_value(token, languages)
+            .and_then(|value| value.as_bool())
+    }
+
+    /**
+     * Lookup an integer value, returning `None` when the value isn't an integer
+     */
+    pub fn lookup_i64(&self, token: &str, languages: &[Language]) -> Option<i64> {
+        self.lookup_value(token, languages)
+            .and_then(|value| value.as_i64())
+    }
+
+    /**
+     * Lookup a numeric value, returning `None` when the value isn't a number
+     *
+     * Integers are converted to floating point values.
+     */
+    pub fn lookup_f64(&self, token: &str, languages: &[Language]) -> Option<f64> {
+        self.lookup_value(token, languages)
+            .and_then(|value| value.as_f64())
+    }
+
+    /**
+     * Render a number with the thousands and decimal separators of the first of the languages
+     * which has number formatting conventions, e.g. `1234567.89` is rendered as "1,234,567.89"
+     * for `en` and "1.234.567,89" for `de`
+     *
+     * The built-in conventions of `format::number` cover `en`, `de` and `fr`. The separators of a
+     * language can be set, or overridden, with a top-level `_format` key in its translations:
+     *
+     * ```yaml
+     * _format:
+     *   thousands_separator: "'"
+     *   decimal_separator: "."
+     * ```
+     *
+     * The languages are consulted with the same fallbacks as `localize`, and English conventions
+     * are used when none of them have any.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * assert_eq!(y10n.format_number(1234567.89, &["de".into()]), "1.234.567,89");
+     * ```
+     */
+    pub fn format_number(&self, value: f64, languages: &[Language]) -> String {
+        for key in self.chain(languages) {
+            let configured = self
+                .translations
+                .get(&key)
+                .and_then(|translations| translations.get(FORMAT_KEY));
+            let code = Language::from(&key).map_or(key.clone(), |lang| lang.code);
+            let builtin = format::separators(&code);
+            if configured.is_none() && builtin.is_none() {
+                continue;
+            }
+
+            let (thousands, decimal) = builtin.unwrap_or((",", "."));
+            let separator = |name: &str, default| {
+                configured
+                    .and_then(|configured| configured.get(name))
+                    .and_then(|separator| separator.as_str())
+                    .unwrap_or(default)
+            };
+            return format::with_separators(
+                value,
+                separator("thousands_separator", thousands),
+                separator("decimal_separator", decimal),
+            );
+        }
+        format::with_separators(value, ",", ".")
+    }
+
+    /**
+     * Return the direction of the text for the given languages, which is the direction of the
+     * first loaded language consulted by `localize`
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * assert_eq!(y10n.direction(&["ar".into(), "de".into()]), Direction::Ltr);
+     * ```
+     *
+     * Left to right is returned when none of the languages are loaded.
+     */
+    pub fn direction(&self, languages: &[Language]) -> Direction {
+        self.chain(languages)
+            .into_iter()
+            .find(|key| self.translations.contains_key(key))
+            .and_then(|key| Language::from(&key).ok())
+            .map_or(Direction::Ltr, |lang| lang.direction())
+    }
+
+    /**
+     * Negotiate the languages to use for a raw `Accept-Language` header
+     *
+     * The header is parsed, sorted by descending quality (preserving header order for ties) and
+     * then filtered down to only the languages which have actually been loaded. A requested
+     * language with a region, such as `en-US`, will match an `en-US` translation file first and
+     * otherwise fall back to the bare `en` file.
+     *
+     * The returned `Language` instances describe the loaded translations which matched, so they can
+     * be passed straight into `localize`. Languages marked with `set_fallback_only` are never
+     * returned.
+     */
+    pub fn negotiate(&self, header: &str) -> Vec<Language> {
+        self.loaded(&parse_accept_language(header))
+    }
+
+    /**
+     * Negotiate the languages to use for already parsed languages, such as those stored in a
+     * user's preferences
+     *
+     * Only the requested languages which have been loaded are returned, in the order they were
+     * requested. A requested language with a region, such as `en-US`, matches an `en-US`
+     * translation file first and otherwise falls back to the bare `en` file, in which case `en` is
+     * returned.
+     *
+     * When none of the requested languages have been loaded the default language is returned (see
+     * `set_default_language`), or no languages at all if there is no default.
+     *
+     * ```rust
+     * use y10n::*;
+     * let y10n = Y10n::from_glob("l10n/**/*.yml").unwrap();
+     * let negotiated = y10n.negotiate_languages(&parse_accept_language("fr, de-AT, en"));
+     * let keys: Vec<String> = negotiated.iter().map(|l| l.to_string()).collect();
+     * assert_eq!(keys, vec!["de", "en"]);
+     * ```
+     */
+    pub fn negotiate_languages(&self, requested: &[Language]) -> Vec<Language> {
+        let mut negotiated = self.loaded(requested);
+        if negotiated.is_empty() {
+            if let Some(default) = self.default_language() {
+                if self.translations.contains_key(&default) {
+                    if let Ok(language) = Language::from(&default) {
+                        negotiated.push(language);
+                    }
+                }
+            }
+        }
+        negotiated
+    }
+
+    /**
+     * Return the loaded languages which match the requested languages, in the requested order
+     */
+    fn loaded(&self, requested: &[Language]) -> Vec<Language> {
+        let mut negotiated: Vec<Language> = vec![];
+
+        for lang in requested {
+            for key in self.expand(lang) {
+                if self.translations.contains_key(&key) && !self.fallback_only.contains(&key) {
+                    if !negotiated.iter().any(|n| n.key() == key) {
+                        if let Ok(mut matched) = Language::from(&key) {
+                            matched.quality = lang.quality;
+                            negotiated.push(matched);
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        negotiated
+    }
+
+    /**
+     * Return the single best loaded language for a raw `Accept-Language` header, which is the
+     * first language returned by `negotiate`
+     *
+     * This is the language to advertise in a `Content-Language` response header.
+     */
+    pub fn best_match(&self, header: &str) -> Option<Language> {
+        self.negotiate(header).into_iter().next()
+    }
+}
+
+thread_local! {
+    static LANGUAGES: RefCell<Vec<Language>> = const { RefCell::new(vec![]) };
+}
+
+/**
+ * Run the closure with the languages as the ambient languages of the current thread
+ *
+ * Methods such as `Y10n::lookup` use the ambient languages when called with no languages, which
+ * avoids threading the languages through deep call stacks. The previous ambient languages are
+ * restored once the closure returns, so calls can be nested.
+ *
+ * ```rust
+ * use y10n::*;
+ * let y10n = Y10n::from_sources(&[("de", b"greeting: 'moin moin'")], Format::Yaml).unwrap();
+ * let greeting = with_languages(&["de".into()], || y10n.lookup("greeting", &[]).map(String::from));
+ * assert_eq!(greeting.as_deref(), Some("moin moin"));
+ * ```
+ *
+ * The languages are local to the thread, asynchronous tasks which may move between threads should
+ * pass their languages explicitly.
+ */
+pub fn with_languages<R, F: FnOnce() -> R>(languages: &[Language], f: F) -> R {
+    /// Restores the previous ambient languages, even when the closure panics
+    struct Restore(Vec<Language>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = std::mem::take(&mut self.0);
+            LANGUAGES.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let previous = LANGUAGES.with(|current| current.replace(languages.to_vec()));
+    let _restore = Restore(previous);
+    f()
+}
+
+/**
+ * Return the ambient languages of the current thread set by `with_languages`
+ */
+pub fn current_languages() -> Vec<Language> {
+    LANGUAGES.with(|current| current.borrow().clone())
+}
+
+/**
+ * Turn a localization key into a human readable form
+ *
+ * The last segment of a dotted key is split into words on underscores, hyphens, and camelCase
+ * boundaries which are then title-cased, e.g. `user_profile.edit_button` becomes "Edit Button".
+ */
+pub fn humanize_key(key: &str) -> String {
+    let segment = key.rsplit('.').next().unwrap_or(key);
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut previous_lowercase = false;
+
+    for c in segment.chars() {
+        if c == '_' || c == '-' || c.is_whitespace() {
+            words.push(std::mem::take(&mut word));
+            previous_lowercase = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lowercase {
+            words.push(std::mem::take(&mut word));
+        }
+        previous_lowercase = c.is_lowercase() || c.is_ascii_digit();
+        word.push(c);
+    }
+    words.push(word);
+
+    words
+        .iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/**
+ * Parse a string containing the value of an Accept-Language header
+ *
+ * For example: 'en,de;q=0.5`
+ *
+ * The languages are sorted by descending quality, keeping the order of the header for languages
+ * with the same quality, and languages with `q=0` are dropped since they're not acceptable.
+ *
+ * A language which is repeated in the header is only returned once, with its highest quality, so
+ * `en,en;q=0.8,en-US` returns `en` and `en-US`. Languages are compared by their code and region,
+ * ignoring case, so `en-US` and `en-GB` are both kept.
+ */
+pub fn parse_accept_language(header: &str) -> Vec<Language> {
+    trace!("Parsing languages from: {}", header);
+    let mut results = vec![];
+
+    for part in header.split(",") {
+        match Language::from(part) {
+            Ok(language) => results.push(language),
+            Err(_) if part.trim().is_empty() => {}
+            Err(_) => warn!("Dropping unparseable language: {:?}", part),
+        }
+    }
+    sort_by_quality(&mut results);
+    results
+}
+
+/**
+ * Parse a string containing the value of an Accept-Language header, returning a `ParseError` for
+ * each segment which isn't a well-formed language tag instead of dropping it
+ *
+ * This is useful for diagnosing the headers which clients or proxies actually send, for example
+ * `en,;;garbage,de;q=2` reports both `;;garbage` and `de;q=2`. Segments are validated like
+ * `parse_accept_language_strict`, and empty segments, such as the one left by a trailing comma,
+ * are ignored. The languages are sorted and filtered by quality just like `parse_accept_language`.
+ *
+ * ```rust
+ * use y10n::*;
+ * let errors = try_parse_accept_language("en,;;garbage").unwrap_err();
+ * assert_eq!(errors[0].segment, ";;garbage");
+ * assert_eq!(try_parse_accept_language("en,de;q=0.5").map(|langs| langs.len()), Ok(2));
+ * ```
+ */
+pub fn try_parse_accept_language(header: &str) -> Result<Vec<Language>, Vec<ParseError>> {
+    trace!("Parsing languages from: {}", header);
+    let mut results = vec![];
+    let mut errors = vec![];
+
+    for part in header.split(',').filter(|part| !part.trim().is_empty()) {
+        match Language::from_strict(part) {
+            Ok(language) => results.push(language),
+            Err(_) => {
+                let tag = part.split(';').next().unwrap_or_default();
+                let reason = if STRICT_LANG_REGEX.is_match(tag) {
+                    "the quality must be a number from 0 to 1"
+                } else {
+                    "not a well-formed language tag"
+                };
+                errors.push(ParseError {
+                    segment: part.trim().to_string(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    sort_by_quality(&mut results);
+    Ok(results)
+}
+
+/**
+ * Parse a string containing the value of an Accept-Language header, validating each language tag
+ * against the structure of BCP 47
+ *
+ * Unlike `parse_accept_language` any segment which is not a well-formed tag, such as `english` or
+ * `en-US;q=2`, is dropped. The languages are sorted and filtered by quality just the same.
+ */
+pub fn parse_accept_language_strict(header: &str) -> Vec<Language> {
+    trace!("Strictly parsing languages from: {}", header);
+    let mut results = vec![];
+
+    for part in header.split(',') {
+        match Language::from_strict(part) {
+            Ok(language) => results.push(language),
+            Err(_) => debug!("Dropping invalid language tag: {:?}", part),
+        }
+    }
+    sort_by_quality(&mut results);
+    results
+}
+
+/**
+ * Drop the languages which are not acceptable (`q=0`), stably sort the rest by descending
+ * quality, and drop the repeated occurrences of each language key after its highest quality one
+ */
+fn sort_by_quality(languages: &mut Vec<Language>) {
+    languages.retain(|lang| lang.quality > 0.0);
+    languages.sort_by(|a, b| b.quality.partial_cmp(&a.quality).unwrap_or(Ordering::Equal));
+
+    let mut seen = HashSet::new();
+    languages.retain(|lang| seen.insert(lang.key()));
+}
+
+/**
+ * Locale ontains useful information about a locale
+ */
+#[derive(Clone, Debug)]
+pub struct Language {
+    /// A non-region based language code, i.e. "en" or "de"
+    pub code: String,
+    script: Option<String>,
+    region: Option<String>,
+    quality: f64,
+}
+
+impl Language {
+    /**
+     * Create a `Language` for a language code, e.g. `Language::new("en")`, with a quality of `1`
+     *
+     * The code is lowercased like a parsed language. This panics when the code is empty, but
+     * doesn't otherwise validate it, use `try_new` for codes which come from callers.
+     */
+    pub fn new(code: impl Into<String>) -> Language {
+        let code = code.into();
+        assert!(!code.is_empty(), "A language code must not be empty");
+        Language {
+            code: code.to_lowercase(),
+            script: None,
+            region: None,
+            quality: 1.0,
+        }
+    }
+
+    /**
+     * Create a `Language` for a language code and region, e.g.
+     * `Language::with_region("en", "US")`, with a quality of `1`
+     *
+     * The region is uppercased like a parsed language. This panics when the code or region is
+     * empty, use `try_with_region` for subtags which come from callers.
+     */
+    pub fn with_region(code: impl Into<String>, region: impl Into<String>) -> Language {
+        let region = region.into();
+        assert!(!region.is_empty(), "A region must not be empty");
+        Language {
+            region: Some(region.to_uppercase()),
+            ..Language::new(code)
+        }
+    }
+
+    /**
+     * Create a `Language` like `new`, returning `Error::InvalidLanguage` unless the code is 2 or 3
+     * ASCII letters like the language subtag of a BCP 47 tag, e.g. for `en-US` or `e1`
+     */
+    pub fn try_new(code: impl Into<String>) -> Result<Language, Error> {
+        let code = code.into();
+        if (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphabetic()) {
+            Ok(Language::new(code))
+        } else {
+            Err(Error::InvalidLanguage(code))
+        }
+    }
+
+    /**
+     * Create a `Language` like `with_region`, returning `Error::InvalidLanguage` unless the code is
+     * valid for `try_new` and the region is 2 ASCII letters or 3 digits, such as `419`
+     */
+    pub fn try_with_region(
+        code: impl Into<String>,
+        region: impl Into<String>,
+    ) -> Result<Language, Error> {
+        let language = Language::try_new(code)?;
+        let region = region.into();
+        let letters = region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic());
+        let digits = region.len() == 3 && region.chars().all(|c| c.is_ascii_digit());
+        if letters || digits {
+            Ok(Language {
+                region: Some(region.to_uppercase()),
+                ..language
+            })
+        } else {
+            Err(Error::InvalidLanguage(format!(
+                "{}-{}",
+                language.code, region
+            )))
+        }
+    }
+
+    /**
+     * Set the quality of the language, e.g. `Language::new("de").with_quality(0.5)` for `de;q=0.5`
+     *
+     * This replaces the former `Language::new(code, region, quality)` constructor, so a language
+     * built from all of its components is `Language::with_region("en", "GB").with_quality(0.5)`.
+     */
+    pub fn with_quality(mut self, quality: f64) -> Language {
+        self.quality = quality;
+        self
+    }
+
+    /**
+     * Create a `Language` instance from a segment of an `Accepts-Language` header
+     *
+     * For example `en`, `de;q=0.5` or `zh-Hant-TW`. The language code is lowercased, the script
+     * titlecased and the region uppercased, so `EN-us` is parsed as `en-US`.
+     */
+    fn from(segment: &str) -> Result<Language, Error> {
+        if let Some(wildcard) = Language::wildcard(segment) {
+            return Ok(wildcard);
+        }
+
+        if let Some(captures) = LANG_REGEX.captures(segment) {
+            Ok(Language {
+                code: captures
+                    .name("code")
+                    .map_or("unknown".to_string(), |c| c.as_str().to_lowercase()),
+                script: captures.name("script").map(|c| titlecase(c.as_str())),
+                region: captures.name("region").map(|c| c.as_str().to_uppercase()),
+                quality: captures
+                    .name("quality")
+                    .map_or(1.0, |c| c.as_str().parse().unwrap_or(0.0)),
+            })
+        } else {
+            Err(Error::InvalidLanguage(segment.to_string()))
+        }
+    }
+
+    /**
+     * Create a `Language` instance from a segment of an `Accepts-Language` header which must be a
+     * well-formed BCP 47 language tag
+     */
+    fn from_strict(segment: &str) -> Result<Language, Error> {
+        if let Some(wildcard) = Language::wildcard(segment) {
+            if (0.0..=1.0).contains(&wildcard.quality) {
+                return Ok(wildcard);
+            }
+        }
+
+        let captures = STRICT_LANG_REGEX
+            .captures(segment)
+            .ok_or_else(|| Error::InvalidLanguage(segment.to_string()))?;
+
+        Ok(Language {
+            code: captures["code"].to_lowercase(),
+            script: captures.name("script").map(|c| titlecase(c.as_str())),
+            region: captures.name("region").map(|c| c.as_str().to_uppercase()),
+            quality: captures
+                .name("quality")
+                .map_or(1.0, |c| c.as_str().parse().unwrap_or(0.0)),
+        })
+    }
+
+    /**
+     * Create the wildcard language from a `*` segment of an `Accept-Language` header, e.g.
+     * `*;q=0.1`
+     */
+    fn wildcard(segment: &str) -> Option<Language> {
+        let params = segment.trim().strip_prefix('*')?;
+        let quality = match params.trim() {
+            "" => 1.0,
+            params => params
+                .strip_prefix(';')?
+                .trim()
+                .strip_prefix("q=")?
+                .trim()
+                .parse()
+                .ok()?,
+        };
+
+        Some(Language {
+            code: WILDCARD.to_string(),
+            script: None,
+            region: None,
+            quality,
+        })
+    }
+
+    /**
+     * The direction which the language is written in, which is right to left for languages such
+     * as Arabic (`ar`), Hebrew (`he`), Persian (`fa`) and Urdu (`ur`)
+     */
+    pub fn direction(&self) -> Direction {
+        if RTL_CODES.contains(&self.code.to_lowercase().as_str()) {
+            Direction::Rtl
+        } else {
+            Direction::Ltr
+        }
+    }
+
+    /**
+     * Whether the language is written from right to left, see `direction`
+     */
+    pub fn is_rtl(&self) -> bool {
+        self.direction() == Direction::Rtl
+    }
+
+    /**
+     * Whether this is the wildcard language `*`, which matches any language
+     */
+    pub fn is_wildcard(&self) -> bool {
+        self.code == WILDCARD
+    }
+
+    /**
+     * The key which a translation file for this language would be loaded under, e.g. `en-US` or
+     * `zh-Hant-TW`
+     */
+    fn key(&self) -> String {
+        let mut key = self.code.clone();
+        for subtag in self.script.iter().chain(self.region.iter()) {
+            key.push('-');
+            key.push_str(subtag);
+        }
+        key
+    }
+
+    /**
+     * The translation keys to consult for this language in order of preference
+     *
+     * A language with a region will try the regional key first (`en-US`) and then the bare
+     * language code (`en`). A language with a script tries the key without the region in between,
+     * so `zh-Hant-TW` falls back to `zh-Hant` and then `zh`.
+     */
+    fn fallback_keys(&self) -> Vec<String> {
+        let mut keys = vec![self.key()];
+        if let (Some(script), Some(_)) = (&self.script, &self.region) {
+            keys.push(format!("{}-{}", self.code, script));
+        }
+        if self.script.is_some() || self.region.is_some() {
+            keys.push(self.code.clone());
+        }
+        keys
+    }
+}
+
+/**
+ * Normalize the casing of a language key derived from a file name, following the BCP 47
+ * conventions of a lowercase language code, titlecase script, and uppercase region, e.g.
+ * `EN-us` becomes `en-US`
+ */
+fn normalize_key(key: &str) -> String {
+    key.split('-')
+        .enumerate()
+        .map(|(i, subtag)| {
+            if i == 0 {
+                subtag.to_lowercase()
+            } else if subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+                titlecase(subtag)
+            } else {
+                subtag.to_uppercase()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/**
+ * Titlecase an ASCII script subtag, e.g. `hant` becomes `Hant`
+ */
+fn titlecase(subtag: &str) -> String {
+    let (first, rest) = subtag.split_at(1.min(subtag.len()));
+    format!("{}{}", first.to_uppercase(), rest.to_lowercase())
+}
+
+/**
+ * Parse a language in the same form as a segment of an `Accept-Language` header, e.g. `en-US` or
+ * `de;q=0.5`
+ */
+impl std::str::FromStr for Language {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Language::from(s.trim())
+    }
+}
+
+/**
+ * Format the language in its canonical form, e.g. `en-US;q=0.5`, omitting the region when there
+ * is none and the quality when it's the default of `1`
+ */
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.key())?;
+        if self.quality != 1.0 {
+            write!(f, ";q={}", self.quality)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Serialize the language as a single string in its canonical form, e.g. `"en-US;q=0.5"`
+ */
+impl serde::Serialize for Language {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/**
+ * Deserialize the language from a single string, parsed in the same way as `FromStr`
+ */
+impl<'de> serde::Deserialize<'de> for Language {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let segment = String::deserialize(deserializer)?;
+        segment.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/**
+ * Parse the string leniently in the same way as `FromStr`, so `"EN-us".into()` is `en-US`
+ *
+ * A string which can't be parsed at all, such as `""`, is used as the lowercased language code
+ * rather than failing. Use `parse` or `Language::try_new` to reject invalid languages.
+ */
+impl From<&str> for Language {
+    fn from(tag: &str) -> Self {
+        Language::from(tag.trim()).unwrap_or_else(|_| Self {
+            code: tag.to_lowercase(),
+            script: None,
+            region: None,
+            quality: 1.0,
+        })
+    }
+}
+
+/**
+ * A plural mapping which is missing categories required by its language
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluralGap {
+    /// The language key of the translations, e.g. `ru`
+    pub language: String,
+    /// The dotted key of the plural mapping
+    pub key: String,
+    /// The required categories which the mapping doesn't provide
+    pub missing: Vec<plural::PluralCategory>,
+}
+
+/**
+ * A branch of a plural or select mapping which doesn't use all of the placeholders of its siblings
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchMismatch {
+    /// The language key of the translations, e.g. `en`
+    pub language: String,
+    /// The dotted key of the mapping
+    pub key: String,
+    /// The branch which is missing placeholders, e.g. `other`
+    pub branch: String,
+    /// The placeholders used by sibling branches which this branch doesn't use
+    pub missing: Vec<String>,
+}
+
+/**
+ * A string which doesn't use the same placeholders as the string with the same key in the base
+ * language, see `Y10n::validate_placeholders`
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaceholderMismatch {
+    /// The language key of the translations, e.g. `de`
+    pub language: String,
+    /// The dotted key of the string
+    pub key: String,
+    /// The placeholders of the base language's string which this string doesn't use
+    pub missing: Vec<String>,
+    /// The placeholders this string uses which the base language's string doesn't
+    pub extra: Vec<String>,
+}
+
+/**
+ * A segment of an `Accept-Language` header which couldn't be parsed, see
+ * `try_parse_accept_language`
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// The offending segment of the header, e.g. `en-US;q=2`
+    pub segment: String,
+    /// Why the segment couldn't be parsed
+    pub reason: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid language {:?}: {}", self.segment, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/**
+ * Statistics about the loaded translations relative to a base language, see `Y10n::stats`
+ */
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct TranslationStats {
+    /// The language key of the base language, e.g. `en`
+    pub base: String,
+    /// The number of keys of the base language
+    pub base_keys: usize,
+    /// The statistics of every loaded language, including the base language, by language key
+    pub languages: std::collections::BTreeMap<String, LanguageStats>,
+}
+
+/**
+ * Statistics about the translations of a single language, see `Y10n::stats`
+ */
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct LanguageStats {
+    /// The number of keys in the language's own translations
+    pub keys: usize,
+    /// The number of keys of the base language which the language provides
+    pub translated: usize,
+    /// The percentage of keys of the base language which the language provides, from 0 to 100
+    pub coverage: f64,
+}
+
+/**
+ * A problem with the translations found by `Y10n::verify`
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// The reference language isn't loaded
+    MissingReference(String),
+    /// A language lacks keys which the reference language has, see `Y10n::missing_keys`
+    MissingKeys {
+        /// The language key of the translations, e.g. `de`
+        language: String,
+        /// The dotted keys which are missing
+        keys: Vec<String>,
+    },
+    /// A plural mapping is missing categories required by its language
+    PluralGap(PluralGap),
+    /// A branch of a plural or select mapping doesn't use all of its siblings' placeholders
+    BranchMismatch(BranchMismatch),
+    /// A string doesn't use the same placeholders as the reference language's string
+    PlaceholderMismatch(PlaceholderMismatch),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::MissingReference(language) => {
+                write!(f, "The reference language {} isn't loaded", language)
+            }
+            ValidationError::MissingKeys { language, keys } => {
+                write!(f, "{} is missing the keys: {}", language, keys.join(", "))
+            }
+            ValidationError::PluralGap(gap) => write!(
+                f,
+                "{}: {} is missing the plural categories: {}",
+                gap.language,
+                gap.key,
+                gap.missing
+                    .iter()
+                    .map(|category| category.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ValidationError::BranchMismatch(mismatch) => write!(
+                f,
+                "{}: {}.{} is missing the placeholders: {}",
+                mismatch.language,
+                mismatch.key,
+                mismatch.branch,
+                mismatch.missing.join(", ")
+            ),
+            ValidationError::PlaceholderMismatch(mismatch) => {
+                write!(f, "{}: {}", mismatch.language, mismatch.key)?;
+                if !mismatch.missing.is_empty() {
+                    write!(
+                        f,
+                        " is missing the placeholders: {}",
+                        mismatch.missing.join(", ")
+                    )?;
+                }
+                if !mismatch.extra.is_empty() {
+                    if !mismatch.missing.is_empty() {
+                        write!(f, " and")?;
+                    }
+                    write!(
+                        f,
+                        " has unknown placeholders: {}",
+                        mismatch.extra.join(", ")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/**
+ * The formats which translations can be parsed from
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// YAML documents, such as the contents of `en.yml`
+    Yaml,
+    /// TOML documents, such as the contents of `en.toml`, enabled with the `toml` feature
+    #[cfg(feature = "toml")]
+    Toml,
+    /// gettext `.po` files, such as the contents of `de.po`, enabled with the `gettext` feature
+    #[cfg(feature = "gettext")]
+    Gettext,
+}
+
+impl Format {
+    /**
+     * The format of a translation file according to its extension, which is YAML for anything
+     * other than `.toml` or `.po`
+     */
+    fn of(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "toml")]
+            Some("toml") => Format::Toml,
+            #[cfg(feature = "gettext")]
+            Some("po") => Format::Gettext,
+            _ => Format::Yaml,
+        }
+    }
+}
+
+/**
+ * Options which control how translation files are processed as they are loaded
+ */
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    /// How line endings inside of loaded strings should be handled
+    pub line_endings: LineEndings,
+    /// How keys declared more than once within a single file should be handled
+    pub duplicate_keys: DuplicateKeys,
+    /// How tags outside of the YAML core schema, such as `!something`, should be handled
+    pub unknown_tags: UnknownTags,
+}
+
+/**
+ * The treatment of YAML tags outside of the core schema, such as `!something`
+ *
+ * Such tags are always stripped from the loaded values, since translations have no use for them.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UnknownTags {
+    /// Strip unknown tags without reporting them
+    Ignore,
+    /// Log a warning for each unknown tag and strip it
+    #[default]
+    Warn,
+    /// Fail to load the file with `Error::UnknownTags`
+    Deny,
+}
+
+/**
+ * The treatment of keys which are declared more than once within a single file, where the last
+ * declaration silently wins
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DuplicateKeys {
+    /// Load the file without checking for duplicates
+    Allow,
+    /// Log a warning for each duplicated key and load the file
+    #[default]
+    Warn,
+    /// Fail to load the file with `Error::DuplicateKeys`
+    Deny,
+}
+
+/**
+ * The treatment of line endings inside of loaded strings
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEndings {
+    /// Leave strings exactly as they were written
+    #[default]
+    Preserve,
+    /// Convert `\r\n` and lone `\r` line endings to `\n`
+    Lf,
+    /// Convert `\n` and lone `\r` line endings to `\r\n`
+    CrLf,
+}
+
+impl LineEndings {
+    fn normalize(&self, s: &str) -> String {
+        let lf = s.replace("\r\n", "\n").replace('\r', "\n");
+        match self {
+            LineEndings::Preserve => s.to_string(),
+            LineEndings::Lf => lf,
+            LineEndings::CrLf => lf.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/**
+ * Errors which can occur while loading translations or parsing languages
+ */
+#[derive(Debug)]
+pub enum Error {
+    /// A translation file could not be read
+    Io(std::io::Error),
+    /// A translation file could not be deserialized
+    Yaml(serde_yaml::Error),
+    /// A translation file declared the contained dotted keys more than once
+    DuplicateKeys(Vec<String>),
+    /// A translation file contained the tags outside of the YAML core schema
+    UnknownTags(Vec<String>),
+    /// The glob pattern for loading translations was invalid
+    Pattern {
+        /// The offending pattern
+        pattern: String,
+        /// The underlying error describing what is wrong with the pattern
+        source: glob::PatternError,
+    },
+    /// The translation files could not be watched for changes
+    #[cfg(feature = "watch")]
+    Watch(notify::Error),
+    /// A TOML translation file could not be deserialized
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    /// A gettext `.po` translation file could not be parsed
+    #[cfg(feature = "gettext")]
+    Gettext {
+        /// The line of the file which couldn't be parsed, starting at 1
+        line: usize,
+        /// What is wrong with the line
+        reason: String,
+    },
+    /// Translations for the requested region, e.g. `en-US`, aren't loaded in strict region mode
+    MissingRegion(String),
+    /// A language tag couldn't be parsed
+    InvalidLanguage(String),
+    /// No translations were loaded, e.g. because the glob pattern matched no files
+    NoTranslationsFound,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "Failed to read translations: {}", e),
+            Error::Yaml(e) => write!(f, "Failed to deserialize translations: {}", e),
+            Error::DuplicateKeys(keys) => write!(f, "Duplicated keys: {}", keys.join(", ")),
+            Error::UnknownTags(tags) => write!(f, "Unknown tags: {}", tags.join(", ")),
+            Error::Pattern { pattern, source } => {
+                write!(f, "Invalid glob pattern {:?}: {}", pattern, source)
+            }
+            #[cfg(feature = "watch")]
+            Error::Watch(e) => write!(f, "Failed to watch translations: {}", e),
+            #[cfg(feature = "toml")]
+            Error::Toml(e) => write!(f, "Failed to deserialize translations: {}", e),
+            #[cfg(feature = "gettext")]
+            Error::Gettext { line, reason } => {
+                write!(
+                    f,
+                    "Failed to parse translations on line {}: {}",
+                    line, reason
+                )
+            }
+            Error::MissingRegion(key) => write!(f, "No translations for the region: {}", key),
+            Error::InvalidLanguage(tag) => write!(f, "Invalid language tag: {:?}", tag),
+            Error::NoTranslationsFound => write!(f, "No translations found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Yaml(e) => Some(e),
+            #[cfg(feature = "toml")]
+            Error::Toml(e) => Some(e),
+            Error::Pattern { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[cfg(feature = "watch")]
+impl From<notify::Error> for Error {
+    fn from(e: notify::Error) -> Self {
+        Error::Watch(e)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        Error::Yaml(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
+
+/**
+ * Parse a single translation file in the format of its extension
+ */
+fn parse_file(path: &Path, options: &LoadOptions) -> Result<serde_yaml::Value, Error> {
+    let file = File::open(path)?;
+    match Format::of(path) {
+        Format::Yaml => parse_yaml(file, options),
+        #[cfg(feature = "toml")]
+        Format::Toml => parse_toml(file, options),
+        #[cfg(feature = "gettext")]
+        Format::Gettext => parse_gettext(file, options),
+    }
+}
+
+/**
+ * Parse a single TOML translation document into the same structure as YAML translations, with
+ * tables becoming nested mappings
+ */
+#[cfg(feature = "toml")]
+fn parse_toml<R: std::io::Read>(
+    mut reader: R,
+    options: &LoadOptions,
+) -> Result<serde_yaml::Value, Error> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let mut value = toml::from_str(&source)?;
+    if options.line_endings != LineEndings::Preserve {
+        map_strings(&mut value, &|s| options.line_endings.normalize(s));
+    }
+    Ok(value)
+}
+
+/**
+ * Parse a single translation document, applying the given `LoadOptions` to the result
+ */
+fn parse_yaml<R: std::io::Read>(
+    mut reader: R,
+    options: &LoadOptions,
+) -> Result<serde_yaml::Value, Error> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    if options.duplicate_keys != DuplicateKeys::Allow {
+        let duplicates = duplicates::find(&source);
+        if !duplicates.is_empty() {
+            if options.duplicate_keys == DuplicateKeys::Deny {
+                return Err(Error::DuplicateKeys(duplicates));
+            }
+            for key in duplicates {
+                warn!("The key `{}` is declared more than once", key);
+            }
+        }
+    }
+
+    if options.unknown_tags != UnknownTags::Ignore {
+        let unknown = tags::find_unknown(&source);
+        if !unknown.is_empty() {
+            if options.unknown_tags == UnknownTags::Deny {
+                return Err(Error::UnknownTags(unknown));
+            }
+            for tag in unknown {
+                warn!("Ignoring the unknown tag {}", tag);
+            }
+        }
+    }
+
+    let mut value = serde_yaml::from_str(&source)?;
+    if options.line_endings != LineEndings::Preserve {
+        map_strings(&mut value, &|s| options.line_endings.normalize(s));
+    }
+    Ok(value)
+}
+
+/**
+ * Parse a single gettext `.po` translation document into the same structure as YAML translations
+ */
+#[cfg(feature = "gettext")]
+fn parse_gettext<R: std::io::Read>(
+    mut reader: R,
+    options: &LoadOptions,
+) -> Result<serde_yaml::Value, Error> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let mut value = gettext::parse(&source)?;
+    if options.line_endings != LineEndings::Preserve {
+        map_strings(&mut value, &|s| options.line_endings.normalize(s));
+    }
+    Ok(value)
+}
+
+/**
+ * Replace every string in the given value, recursing into mappings and sequences
+ */
+fn map_strings(value: &mut serde_yaml::Value, f: &dyn Fn(&str) -> String) {
+    use serde_yaml::Value;
+
+    match value {
+        Value::String(s) => *s = f(s),
+        Value::Sequence(seq) => seq.iter_mut().for_each(|v| map_strings(v, f)),
+        Value::Mapping(map) => {
+            for (_, v) in map.iter_mut() {
+                map_strings(v, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/**
+ * Convert a YAML value into the equivalent JSON value, stringifying any scalar mapping keys
+ *
+ * Floating point numbers which JSON can't represent, such as `.nan`, become `null`.
+ */
+fn to_json(value: &serde_yaml::Value) -> serde_json::Value {
+    use serde_json::Value as Json;
+    use serde_yaml::Value;
+
+    match value {
+        Value::Null => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Json::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Json::from(u)
+            } else {
+                n.as_f64()
+                    .and_then(serde_json::Number::from_f64)
+                    .map_or(Json::Null, Json::Number)
+            }
+        }
+        Value::String(s) => Json::String(s.clone()),
+        Value::Sequence(seq) => Json::Array(seq.iter().map(to_json).collect()),
+        Value::Mapping(map) => Json::Object(
+            map.iter()
+                .filter_map(|(k, v)| {
+                    let key = match k {
+                        Value::String(s) => s.clone(),
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
+                        Value::Null => "null".to_string(),
+                        _ => {
+                            warn!("Skipping a mapping key which can't be converted to JSON");
+                            return None;
+                        }
+                    };
+                    Some((key, to_json(v)))
+                })
+                .collect(),
+        ),
+    }
+}
+
+/**
+ * Visit every value nested within the given value along with its dotted key
+ *
+ * The visitor returns whether the children of a mapping should be visited as well. Mappings
+ * nested more than `depth` levels deep are not visited.
+ */
+fn visit<'a>(
+    value: &'a serde_yaml::Value,
+    prefix: &str,
+    depth: usize,
+    visitor: &mut dyn FnMut(&str, &'a serde_yaml::Value) -> bool,
+) {
+    if let Some(map) = value.as_mapping() {
+        if depth == 0 {
+            warn!(
+                "Not descending into {}, it exceeds the maximum depth",
+                prefix
+            );
+            return;
+        }
+
+        for (key, child) in map.iter() {
+            let key = match key.as_str() {
+                Some(key) => key.to_string(),
+                None => serde_yaml::to_string(key)
+                    .unwrap_or_default()
+                    .trim_start_matches("---")
+                    .trim()
+                    .to_string(),
+            };
+            let path = match prefix {
+                "" => key,
+                prefix => format!("{}.{}", prefix, key),
+            };
+
+            if visitor(&path, child) {
+                visit(child, &path, depth - 1, visitor);
+            }
+        }
+    }
+}
+
+/**
+ * Find the value for a dotted key in the mapping, preferring a literal key with the dots before
+ * descending into nested mappings segment by segment
+ */
+fn find_path<'a>(map: &'a serde_yaml::Mapping, key: &str) -> Option<&'a serde_yaml::Value> {
+    if let Some(value) = map.get(&serde_yaml::Value::String(key.to_string())) {
+        return Some(value);
+    }
+
+    key.match_indices('.').find_map(|(i, _)| {
+        map.get(&serde_yaml::Value::String(key[..i].to_string()))
+            .and_then(|value| value.as_mapping())
+            .and_then(|nested| find_path(nested, &key[i + 1..]))
+    })
+}
+
+/**
+ * Check whether a mapping looks like a set of select branches, with an `other` branch and only
+ * strings as values
+ */
+fn is_select(branches: &serde_yaml::Mapping) -> bool {
+    branches.contains_key(&serde_yaml::Value::from("other"))
+        && branches.iter().all(|(_, value)| value.is_string())
+}
+
+/**
+ * Whether the top-level key of the translations is reserved for configuration, i.e. `_config`
+ * or `_format`, rather than holding strings
+ */
+fn is_reserved(key: &str) -> bool {
+    key == CONFIG_KEY || key == FORMAT_KEY
+}
+
+/**
+ * Collect every string nested within the value keyed by its dotted key, skipping the reserved
+ * `_config` and `_format` keys and everything beneath them
+ */
+fn strings(value: &serde_yaml::Value, max_depth: usize) -> HashMap<String, &str> {
+    let mut strings = HashMap::new();
+    visit(value, "", max_depth, &mut |key, value| {
+        if is_reserved(key) {
+            return false;
+        }
+        if let Some(s) = value.as_str() {
+            strings.insert(key.to_string(), s);
+        }
+        true
+    });
+    strings
+}
+
+/**
+ * Recursively sort the keys of every mapping in the value
+ */
+fn sort_keys(value: &mut serde_yaml::Value) {
+    use serde_yaml::{Mapping, Value};
+
+    match value {
+        Value::Mapping(map) => {
+            let mut entries: Vec<(Value, Value)> = std::mem::take(map).into_iter().collect();
+            entries.sort_by_cached_key(|(k, _)| match k.as_str() {
+                Some(s) => s.to_string(),
+                None => serde_yaml::to_string(k).unwrap_or_default(),
+            });
+
+            let mut sorted = Mapping::new();
+            for (k, mut v) in entries {
+                sort_keys(&mut v);
+                sorted.insert(k, v);
+            }
+            *map = sorted;
+        }
+        Value::Sequence(seq) => seq.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+/**
+ * Merge a couple of serde_yaml together
+ *
+ * THis code courtesy of https://stackoverflow.com/a/67743348
+ *
+ * Mappings nested more than `depth` levels deep are replaced rather than merged, and sequences are
+ * combined according to the `strategy`.
+ */
+fn merge_yaml(
+    a: &mut serde_yaml::Value,
+    b: serde_yaml::Value,
+    depth: usize,
+    strategy: MergeStrategy,
+) {
+    match (a, b) {
+        (a @ &mut serde_yaml::Value::Mapping(_), b @ serde_yaml::Value::Mapping(_))
+            if depth == 0 =>
+        {
+            warn!("Not merging mappings which exceed the maximum depth");
+            *a = b;
+        }
+        (a @ &mut serde_yaml::Value::Mapping(_), serde_yaml::Value::Mapping(b)) => {
+            let a = a.as_mapping_mut().unwrap();
+            for (k, v) in b {
+                if strategy == MergeStrategy::ConcatSequences
+                    && v.is_sequence()
+                    && a.contains_key(&k)
+                    && a[&k].is_sequence()
+                {
+                    let mut _b = a.get(&k).unwrap().as_sequence().unwrap().to_owned();
+                    _b.append(&mut v.as_sequence().unwrap().to_owned());
+                    a[&k] = serde_yaml::Value::from(_b);
+                    continue;
+                }
+                if !a.contains_key(&k) {
+                    a.insert(k.to_owned(), v.to_owned());
+                } else {
+                    merge_yaml(&mut a[&k], v, depth - 1, strategy);
+                }
+            }
+        }
+        (a, b) => *a = b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_glob_with_directory_layout() {
+        let y10n =
+            Y10n::from_glob_with_layout("fixtures/layout/*/*.yml", Layout::DirectoryIsLanguage)
+                .expect("Failed to load translations");
+        let mut languages = y10n.languages();
+        languages.sort();
+        assert_eq!(languages, vec!["de", "en"]);
+
+        let en = vec!["en".into()];
+        assert_eq!(Some("Home"), y10n.lookup("menu.home", &en));
+        assert_eq!(Some("Imprint"), y10n.lookup("footer.imprint", &en));
+        let de = vec!["de".into()];
+        assert_eq!(Some("Startseite"), y10n.lookup("menu.home", &de));
+        assert_eq!(Some("Impressum"), y10n.lookup("footer.imprint", &de));
+        // Missing German strings fall back to English within the namespace
+        assert_eq!(
+            Some("About"),
+            y10n.lookup("menu.about", &["de".into(), "en".into()])
+        );
+    }
+
+    #[test]
+    fn from_glob_with_file_layout() {
+        let y10n = Y10n::from_glob_with_layout("l10n/*.yml", Layout::FileIsLanguage)
+            .expect("Failed to load translations");
+        assert_eq!(Some("moin moin"), y10n.lookup("greeting", &["de".into()]));
+    }
+
+    #[test]
+    fn from_glob_with_key_closure() {
+        let y10n = Y10n::from_glob_with_key("fixtures/keyed/*.yml", |path| {
+            let name = path.file_name()?.to_str()?;
+            name.strip_prefix("strings.")?
+                .strip_suffix(".yml")
+                .map(String::from)
+        })
+        .expect("Failed to load translations");
+
+        let mut languages = y10n.languages();
+        languages.sort();
+        // `other.fr.yml` doesn't match the naming convention and is skipped
+        assert_eq!(languages, vec!["de", "en"]);
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &["de".into()]));
+    }
+
+    #[test]
+    fn from_globs_merges_stems() {
+        let y10n = Y10n::from_globs(&["fixtures/globs/common/*.yml", "fixtures/globs/pages/*.yml"])
+            .expect("Failed to load translations");
+        let en = vec!["en".into()];
+        let mut languages = y10n.languages();
+        languages.sort();
+        assert_eq!(languages, vec!["de", "en"]);
+        assert_eq!(Some("Home"), y10n.lookup("nav.home", &en));
+        assert_eq!(Some("Contact"), y10n.lookup("nav.contact", &en));
+        assert_eq!(Some("Welcome home"), y10n.lookup("home.title", &en));
+        // The later pattern wins on conflicting scalars
+        assert_eq!(Some("Sign in to continue"), y10n.lookup("login", &en));
+        assert_eq!(Some("Startseite"), y10n.lookup("nav.home", &["de".into()]));
+    }
+
+    #[test]
+    fn language_serde_round_trip() {
+        let languages = vec![
+            Language::with_region("en", "US").with_quality(0.5),
+            Language::new("de"),
+        ];
+        let json = serde_json::to_string(&languages).expect("Failed to serialize");
+        assert_eq!(json, r#"["en-US;q=0.5","de"]"#);
+
+        let parsed: Vec<Language> = serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].code, "en");
+        assert_eq!(parsed[0].region.as_deref(), Some("US"));
+        assert_eq!(parsed[0].quality, 0.5);
+        assert_eq!(parsed[1].to_string(), "de");
+
+        assert!(serde_json::from_str::<Language>(r#""-""#).is_err());
+    }
+
+    #[test]
+    fn negotiate_languages_exact() {
+        let y10n = Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        let requested = vec![Language::with_region("en", "GB"), "en".into()];
+        let keys: Vec<String> = y10n
+            .negotiate_languages(&requested)
+            .iter()
+            .map(|lang| lang.to_string())
+            .collect();
+        assert_eq!(keys, vec!["en-GB", "en"]);
+    }
+
+    #[test]
+    fn negotiate_languages_region_fallback() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        // The requested order is kept even when the qualities disagree
+        let requested = vec![
+            Language::with_region("en", "AU").with_quality(0.5),
+            Language::with_region("de", "AT").with_quality(0.9),
+        ];
+        let keys: Vec<String> = y10n
+            .negotiate_languages(&requested)
+            .iter()
+            .map(|lang| lang.to_string())
+            .collect();
+        assert_eq!(keys, vec!["en;q=0.5", "de;q=0.9"]);
+    }
+
+    #[test]
+    fn negotiate_languages_no_match() {
+        let mut y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        let requested = vec!["fr".into(), "ja".into()];
+        assert!(y10n.negotiate_languages(&requested).is_empty());
+        assert!(y10n.negotiate_languages(&[]).is_empty());
+
+        y10n.set_default_language("de");
+        let negotiated = y10n.negotiate_languages(&requested);
+        assert_eq!(negotiated.len(), 1);
+        assert_eq!(negotiated[0].to_string(), "de");
+    }
+
+    #[test]
+    fn negotiate_languages_regional_default() {
+        let mut y10n =
+            Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        y10n.set_default_language("en-US");
+        let negotiated = y10n.negotiate_languages(&["fr".into()]);
+        assert_eq!(negotiated.len(), 1);
+        assert_eq!(negotiated[0].code, "en");
+        assert_eq!(negotiated[0].to_string(), "en-US");
+        assert_eq!(y10n.lookup("football", &negotiated), Some("soccer"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_glob_toml() {
+        let y10n = Y10n::from_glob("fixtures/toml/*.toml").expect("Failed to load translations");
+        let en = vec!["en".into()];
+        assert_eq!(Some("hello world"), y10n.lookup("greeting", &en));
+        assert_eq!(Some("Home"), y10n.lookup("menu.home", &en));
+        assert_eq!(Some("Settings"), y10n.lookup("menu.account.settings", &en));
+        assert_eq!(Some(3), y10n.lookup_i64("retries", &en));
+        assert_eq!(
+            Some("moin moin"),
+            y10n.lookup("greeting", &["de".into(), "en".into()])
+        );
+        assert_eq!(
+            Some("Home"),
+            y10n.lookup("menu.home", &["de".into(), "en".into()])
+        );
+    }
+
+    #[test]
+    fn localize_json_merged() {
+        let y10n = Y10n::from_glob("fixtures/partial/*.yml").expect("Failed to load translations");
+        let languages = parse_accept_language("de, en-GB");
+        assert_eq!(
+            y10n.localize_json(&languages),
+            serde_json::json!({
+                "menu": {
+                    "file": {"open": "Öffnen", "obsolete": "Veraltet", "close": "Close"},
+                    "help": "Help",
+                },
+                "cart": {"items": {"one": "{{count}} item", "other": "{{count}} items"}},
+                "greeting": "Hallo",
+            })
+        );
+    }
+
+    #[test]
+    fn localize_json_scalars() {
+        let y10n = Y10n::from_pairs(vec![(
+            "en",
+            "count: 3\nratio: 0.5\nenabled: true\nnothing: ~\nlist: [a, 1]\n1: one\nnan: .nan",
+        )])
+        .expect("Failed to load translations");
+        assert_eq!(
+            y10n.localize_json(&["en".into()]),
+            serde_json::json!({
+                "count": 3,
+                "ratio": 0.5,
+                "enabled": true,
+                "nothing": null,
+                "list": ["a", 1],
+                "1": "one",
+                "nan": null,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_accept_language_dedupe() {
+        let keys = |languages: Vec<Language>| -> Vec<String> {
+            languages.iter().map(|lang| lang.to_string()).collect()
+        };
+        assert_eq!(
+            keys(parse_accept_language(
+                "en;q=0.8,en,en-US,EN-us;q=0.9,en-GB;q=0.5"
+            )),
+            vec!["en", "en-US", "en-GB;q=0.5"]
+        );
+        assert_eq!(
+            keys(parse_accept_language("de;q=0.2,fr,de;q=0.7")),
+            vec!["fr", "de;q=0.7"]
+        );
+        assert_eq!(
+            keys(parse_accept_language_strict("en;q=0.8,en")),
+            vec!["en"]
+        );
+    }
+
+    #[test]
+    fn parse_language_case_insensitive() {
+        let languages = parse_accept_language("EN-us, De;q=0.5");
+        assert_eq!(languages[0].to_string(), "en-US");
+        assert_eq!(languages[1].to_string(), "de;q=0.5");
+
+        let strict = parse_accept_language_strict("EN-us");
+        assert_eq!(strict[0].to_string(), "en-US");
+
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        assert_eq!(Some("hello world"), y10n.lookup("greeting", &languages));
+        assert_eq!(y10n.negotiate("EN-us, DE")[0].to_string(), "en");
+    }
+
+    #[test]
+    fn normalize_file_keys() {
+        assert_eq!(normalize_key("EN-us"), "en-US");
+        assert_eq!(normalize_key("es-419"), "es-419");
+        assert_eq!(normalize_key("ZH-hant-tw"), "zh-Hant-TW");
+
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        std::fs::write(dir.path().join("EN-us.yml"), "greeting: howdy").unwrap();
+        let pattern = dir.path().join("*.yml");
+        let y10n = Y10n::from_glob(&pattern.to_string_lossy()).expect("Failed to load");
+        assert_eq!(y10n.languages(), vec!["en-US"]);
+        assert_eq!(
+            Some("howdy"),
+            y10n.lookup("greeting", &parse_accept_language("en-us"))
+        );
+    }
+
+    #[cfg(feature = "gettext")]
+    #[test]
+    fn from_glob_gettext() {
+        let y10n = Y10n::from_glob("fixtures/gettext/*.po").expect("Failed to load translations");
+        let de = parse_accept_language("de, en");
+        assert_eq!(Some("Willkommen"), y10n.lookup("Welcome", &de));
+        assert_eq!(Some("Goodbye"), y10n.lookup("Goodbye", &de));
+        assert_eq!(
+            Some(Cow::from("{{count}} Dateien")),
+            y10n.pluralize("{{count}} file", 3, &de)
+        );
+        assert_eq!(Some("Hallo {{who}}"), y10n.lookup("Hello {{who}}", &de));
+    }
+
+    #[test]
+    fn render_placeholders() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "greeting: 'Hello {{who}}'\ntransfer: '{{ from }} sent {{amount}} to {{to}}'",
+            ),
+            ("de", "greeting: 'Hallo {{who}}'"),
+        ]);
+        let vars: HashMap<String, String> =
+            vec![("who", "Welt"), ("from", "Alice"), ("amount", "1.234,56 €")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        let languages = parse_accept_language("de, en");
+
+        assert_eq!(
+            Some("Hallo Welt".to_string()),
+            y10n.render("greeting", &languages, &vars)
+        );
+        // The missing `to` variable is left untouched
+        assert_eq!(
+            Some("Alice sent 1.234,56 € to {{to}}".to_string()),
+            y10n.render("transfer", &languages, &vars)
+        );
+        assert_eq!(None, y10n.render("missing", &languages, &vars));
+    }
+
+    #[test]
+    fn format_number_locales() {
+        let y10n = y10n_from(&[
+            ("en", "greeting: hello"),
+            ("de", "greeting: hallo"),
+            ("fr", "greeting: bonjour"),
+            (
+                "de-CH",
+                "_format:\n  thousands_separator: \"'\"\n  decimal_separator: '.'",
+            ),
+            ("ja", "greeting: konnichiwa"),
+        ]);
+        let format = |header| y10n.format_number(1234567.89, &parse_accept_language(header));
+
+        assert_eq!(format("en"), "1,234,567.89");
+        assert_eq!(format("de"), "1.234.567,89");
+        assert_eq!(format("fr"), "1\u{202f}234\u{202f}567,89");
+        assert_eq!(format("de-CH"), "1'234'567.89");
+        assert_eq!(format("de-AT"), "1.234.567,89");
+        assert_eq!(format("ja, de"), "1.234.567,89");
+        assert_eq!(format("ja"), "1,234,567.89");
+
+        let localized = y10n.localize(&parse_accept_language("de-CH"));
+        assert!(localized.get(FORMAT_KEY).is_none());
+        assert!(y10n.missing_keys("de-CH").is_empty());
+    }
+
+    #[test]
+    fn language_direction() {
+        assert!(Language::from("ar").unwrap().is_rtl());
+        assert_eq!(Language::from("ar").unwrap().direction(), Direction::Rtl);
+        assert_eq!(
+            parse_accept_language("he-IL")[0].direction(),
+            Direction::Rtl
+        );
+        assert!(!Language::from("en").unwrap().is_rtl());
+        assert_eq!(Language::from("en").unwrap().direction(), Direction::Ltr);
+        assert_eq!(Direction::Rtl.to_string(), "rtl");
+        assert_eq!(Direction::Ltr.to_string(), "ltr");
+    }
+
+    #[test]
+    fn y10n_direction() {
+        let y10n = y10n_from(&[("en", "greeting: hello"), ("ar", "greeting: مرحبا")]);
+        assert_eq!(
+            y10n.direction(&parse_accept_language("ar-EG, en")),
+            Direction::Rtl
+        );
+        assert_eq!(
+            y10n.direction(&parse_accept_language("fa, en")),
+            Direction::Ltr
+        );
+        assert_eq!(y10n.direction(&parse_accept_language("fr")), Direction::Ltr);
+    }
+
+    #[test]
+    fn test_language_new() {
+        let y10n = Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        let language = Language::with_region("en", "GB").with_quality(0.5);
+        assert_eq!(language.key(), "en-GB");
+        assert_eq!(language.quality, 0.5);
+
+        let values = y10n.localize(&[language]);
+        assert_eq!(values["color"].as_str(), Some("colour"));
+
+        let language = Language::new("DE");
+        assert_eq!(language.key(), "de");
+        assert_eq!(language.quality, 1.0);
+        assert_eq!(Language::with_region("pt", "br").to_string(), "pt-BR");
+    }
+
+    #[test]
+    fn test_language_new_three_letters() {
+        assert_eq!(Language::new("fil").key(), "fil");
+        assert_eq!(Language::with_region("es", "419").key(), "es-419");
+    }
+
+    #[test]
+    #[should_panic(expected = "A language code must not be empty")]
+    fn test_language_new_empty() {
+        Language::new("");
+    }
+
+    #[test]
+    #[should_panic(expected = "A region must not be empty")]
+    fn test_language_with_empty_region() {
+        Language::with_region("en", "");
+    }
+
+    #[test]
+    fn test_language_try_new() {
+        let language = Language::try_new("EN").expect("Failed to create the language");
+        assert_eq!(language.key(), "en");
+        let language = Language::try_with_region("es", "419").expect("Failed to create");
+        assert_eq!(language.key(), "es-419");
+
+        for code in &["", "e", "en-US", "e1", "engl"] {
+            assert!(
+                Language::try_new(*code).is_err(),
+                "{:?} should be invalid",
+                code
+            );
+        }
+        for region in &["", "U", "U-S", "1A", "USA"] {
+            assert!(
+                Language::try_with_region("en", *region).is_err(),
+                "{:?} should be invalid",
+                region
+            );
+        }
+    }
+
+    #[test]
+    fn parse_accept_language_by_quality() {
+        let keys = |header| -> Vec<String> {
+            parse_accept_language(header)
+                .iter()
+                .map(|lang| lang.key())
+                .collect()
+        };
+        assert_eq!(keys("de;q=0.3,en;q=0.9"), vec!["en", "de"]);
+        assert_eq!(
+            keys("fr;q=0.5,de,en-US;q=0.5,ja"),
+            vec!["de", "ja", "fr", "en-US"]
+        );
+    }
+
+    #[test]
+    fn parse_accept_language_not_acceptable() {
+        let languages = parse_accept_language("en;q=0,de;q=0.5,fr;q=0.0");
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].code, "de");
+
+        let languages = parse_accept_language_strict("en;q=0, de");
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].code, "de");
+    }
+
+    #[test]
+    fn test_language_parse() {
+        let language: Language = "en-US".parse().expect("Failed to parse");
+        assert_eq!(language.code, "en");
+        assert_eq!(language.region.as_deref(), Some("US"));
+        assert_eq!(language.quality, 1.0);
+
+        let language: Language = " de;q=0.5".parse().expect("Failed to parse");
+        assert_eq!(language.key(), "de");
+        assert_eq!(language.quality, 0.5);
+
+        match "-".parse::<Language>() {
+            Err(Error::InvalidLanguage(tag)) => assert_eq!(tag, "-"),
+            other => panic!("Expected an invalid language, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_language_display() {
+        for canonical in ["en", "en-US", "de;q=0.5", "fr-CA;q=0.25"].iter() {
+            let language: Language = canonical.parse().expect("Failed to parse");
+            assert_eq!(&language.to_string(), canonical);
+        }
+        assert_eq!(Language::new("en").to_string(), "en");
+    }
+
+    #[test]
+    fn test_language_from_str() {
+        let language: Language = "en".into();
+        assert_eq!(language.code, "en");
+
+        let language: Language = "EN".into();
+        assert_eq!(language.code, Language::new("EN").code);
+
+        let language: Language = "EN-us".into();
+        assert_eq!(language.code, "en");
+        assert_eq!(language.to_string(), "en-US");
+        let language: Language = "zh-hant-tw".into();
+        assert_eq!(language.to_string(), "zh-Hant-TW");
+
+        let language: Language = "".into();
+        assert_eq!(language.code, "");
+    }
+
+    #[test]
+    fn test_language_from_str_resolves_regions() {
+        let y10n = Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        assert_eq!(y10n.lookup("football", &["en-US".into()]), Some("soccer"));
+        assert_eq!(y10n.lookup("greeting", &["en-US".into()]), Some("hello"));
+    }
+
+    #[test]
+    fn y10n_from_valid_glob() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        assert_eq!(y10n.languages().len(), 2);
+    }
+
+    #[test]
+    fn y10n_localize() {
+        use serde_yaml::Value;
+
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        let en = Language::from("en").expect("Failed to parse!");
+        let de = Language::from("de").expect("Failed to parse!");
+        let value = y10n.localize(&[de, en]);
+        if let Some(map) = value.as_mapping() {
+            let key = "greeting".into();
+            let greeting = map.get(&key).expect("Failed to find a greeting");
+            assert_eq!(&Value::String("moin moin".to_string()), greeting);
+
+            let secret = map.get(&"secret".into()).expect("Failed to find a secret");
+            assert_eq!(&Value::String("pancakes".to_string()), secret);
+        } else {
+            panic!("The value wasn't a map like I expected");
+        }
+    }
+
+    #[test]
+    fn language_from_segment() {
+        let lang = Language::from("en-US");
+        assert!(lang.is_ok());
+        let lang = lang.unwrap();
+        assert_eq!("en", lang.code);
+        assert_eq!(Some("US".to_string()), lang.region);
+        assert_eq!(1.0, lang.quality);
+    }
+
+    #[test]
+    fn parse_langs_simple() {
+        let header = "en-US,en;q=0.5";
+        let langs = parse_accept_language(header);
+        assert_eq!(langs.len(), 2);
+    }
+
+    #[test]
+    fn parse_langs_multi() {
+        let header = "en-US,en;q=0.7,de-DE;q=0.3";
+        let langs = parse_accept_language(header);
+        assert_eq!(langs.len(), 3);
+        let de = langs.get(2).unwrap();
+        assert_eq!("de", de.code);
+        assert_eq!(0.3, de.quality);
+    }
+
+    #[test]
+    fn parse_langs_script() {
+        let langs = parse_accept_language("zh-hant-tw;q=0.8,fil,en-US");
+        let zh = langs.iter().find(|lang| lang.code == "zh").unwrap();
+        assert_eq!(Some("Hant".to_string()), zh.script);
+        assert_eq!(Some("TW".to_string()), zh.region);
+        assert_eq!(0.8, zh.quality);
+        assert_eq!("zh-Hant-TW;q=0.8", zh.to_string());
+        assert_eq!(zh.fallback_keys(), vec!["zh-Hant-TW", "zh-Hant", "zh"]);
+
+        let fil = langs.iter().find(|lang| lang.code == "fil").unwrap();
+        assert_eq!((None, None), (fil.script.as_deref(), fil.region.as_deref()));
+
+        let en = langs.iter().find(|lang| lang.code == "en").unwrap();
+        assert_eq!(None, en.script);
+        assert_eq!(Some("US".to_string()), en.region);
+        assert_eq!(en.fallback_keys(), vec!["en-US", "en"]);
+    }
+
+    #[test]
+    fn localize_script_chain() {
+        let y10n = y10n_from(&[
+            (
+                "zh",
+                "greeting: ni hao\nfarewell: zai jian\nsecret: jian bing",
+            ),
+            ("zh-Hant", "greeting: 你好\nfarewell: 再見"),
+            ("zh-Hant-TW", "greeting: 哈囉"),
+        ]);
+        let langs = parse_accept_language("zh-Hant-TW");
+        assert_eq!(Some("哈囉"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("再見"), y10n.lookup("farewell", &langs));
+        assert_eq!(Some("jian bing"), y10n.lookup("secret", &langs));
+
+        let localized = y10n.localize(&langs);
+        assert_eq!(Some("哈囉"), localized["greeting"].as_str());
+        assert_eq!(Some("再見"), localized["farewell"].as_str());
+        assert_eq!(Some("jian bing"), localized["secret"].as_str());
+    }
+
+    #[test]
+    fn try_parse_langs_mixed() {
+        let errors = try_parse_accept_language("en-US,,;;garbage, de;q=2 ,english,fr;q=0.5,")
+            .expect_err("Expected the invalid segments to be reported");
+        let segments: Vec<&str> = errors.iter().map(|e| e.segment.as_str()).collect();
+        assert_eq!(segments, vec![";;garbage", "de;q=2", "english"]);
+        assert_eq!(errors[0].reason, "not a well-formed language tag");
+        assert_eq!(errors[1].reason, "the quality must be a number from 0 to 1");
+        assert_eq!(
+            errors[1].to_string(),
+            r#"Invalid language "de;q=2": the quality must be a number from 0 to 1"#
+        );
+
+        let langs = try_parse_accept_language("fr;q=0.5, en-US,,").expect("Failed to parse");
+        let keys: Vec<String> = langs.iter().map(|lang| lang.to_string()).collect();
+        assert_eq!(keys, vec!["en-US", "fr;q=0.5"]);
+
+        // The lenient parser keeps what it can from the same header
+        assert!(!parse_accept_language("en-US,,;;garbage").is_empty());
+    }
+
+    #[test]
+    fn parse_langs_strict() {
+        let header = "zh-Hant-TW;q=0.8, en-419 ,english,en-US;q=2,123,de;q=0.5";
+        let langs = parse_accept_language_strict(header);
+        assert_eq!(langs.len(), 3);
+
+        assert_eq!(Some("419".to_string()), langs[0].region);
+        assert_eq!("zh", langs[1].code);
+        assert_eq!(Some("Hant".to_string()), langs[1].script);
+        assert_eq!(Some("TW".to_string()), langs[1].region);
+        assert_eq!(0.8, langs[1].quality);
+        assert_eq!("de", langs[2].code);
+    }
+
+    /// Build a Y10n from in-memory YAML documents keyed by language
+    fn y10n_from(sources: &[(&str, &str)]) -> Y10n {
+        let mut y10n = Y10n::new();
+        for (key, yaml) in sources {
+            y10n.translations.insert(
+                key.to_string(),
+                serde_yaml::from_str(yaml).expect("Failed to deserialize YAML"),
+            );
+        }
+        y10n
+    }
+
+    #[test]
+    fn lookup_or_found() {
+        let y10n = y10n_from(&[("en", "greeting: hello")]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!("hello", y10n.lookup_or("greeting", &langs, "fallback"));
+    }
+
+    #[test]
+    fn lookup_or_default() {
+        let y10n = y10n_from(&[("en", "greeting: hello")]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!("fallback", y10n.lookup_or("farewell", &langs, "fallback"));
+    }
+
+    #[test]
+    fn from_glob_invalid_pattern() {
+        match Y10n::from_glob("l10n/[") {
+            Err(Error::Pattern { pattern, .. }) => assert_eq!(pattern, "l10n/["),
+            other => panic!("Expected a pattern error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn from_glob_valid() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        assert_eq!(y10n.languages().len(), 2);
+    }
+
+    #[test]
+    fn from_glob_required_empty_directory() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let pattern = dir.path().join("*.yml");
+        let pattern = pattern.to_string_lossy();
+
+        let y10n = Y10n::from_glob(&pattern).expect("Failed to load translations");
+        assert!(y10n.is_empty());
+        assert!(matches!(
+            Y10n::from_glob_required(&pattern),
+            Err(Error::NoTranslationsFound)
+        ));
+        assert!(!Y10n::from_glob_required("l10n/*.yml")
+            .expect("Failed to load translations")
+            .is_empty());
+    }
+
+    #[test]
+    fn merge_overrides() {
+        let mut base = y10n_from(&[
+            ("en", "greeting: hello\nfarewell: bye\nnav:\n  home: Home"),
+            ("de", "greeting: moin"),
+        ]);
+        let overrides = y10n_from(&[
+            ("en", "greeting: howdy\nnav:\n  about: About"),
+            ("fr", "greeting: salut"),
+        ]);
+        base.merge(overrides);
+
+        let en: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("howdy"), base.lookup("greeting", &en));
+        assert_eq!(Some("bye"), base.lookup("farewell", &en));
+        assert_eq!(Some("Home"), base.lookup("nav.home", &en));
+        assert_eq!(Some("About"), base.lookup("nav.about", &en));
+        assert_eq!(Some("moin"), base.lookup("greeting", &["de".into()]));
+        assert_eq!(Some("salut"), base.lookup("greeting", &["fr".into()]));
+    }
+
+    #[test]
+    fn from_pairs_embedded() {
+        let y10n = Y10n::from_pairs(vec![("en", include_str!("../l10n/en.yml"))])
+            .expect("Failed to load translations");
+        assert_eq!(y10n.languages(), vec!["en"]);
+        assert_eq!(Some("pancakes"), y10n.lookup("secret", &["en".into()]));
+    }
+
+    #[test]
+    fn from_pairs_matches_glob_keys() {
+        let from_files = Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load");
+        let from_pairs = Y10n::from_pairs(vec![
+            ("en", include_str!("../fixtures/regions/en.yml")),
+            ("EN-us", include_str!("../fixtures/regions/en-US.yml")),
+            ("en-gb", include_str!("../fixtures/regions/en-GB.yml")),
+            ("en-gb", "greeting: cheerio"),
+        ])
+        .expect("Failed to load translations");
+
+        let mut pair_keys = from_pairs.languages();
+        let mut file_keys = from_files.languages();
+        pair_keys.sort();
+        file_keys.sort();
+        assert_eq!(pair_keys, file_keys);
+
+        let gb = parse_accept_language("en-GB");
+        assert_eq!(Some("colour"), from_pairs.lookup("color", &gb));
+        assert_eq!(Some("cheerio"), from_pairs.lookup("greeting", &gb));
+    }
+
+    #[test]
+    fn from_pairs_errors() {
+        match Y10n::from_pairs(vec![("en".to_string(), "greeting: 'hello")]) {
+            Err(Error::Yaml(_)) => {}
+            other => panic!("Expected a YAML error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn from_str_and_add_language() {
+        let mut y10n = Y10n::from_str("en", "greeting: hello\nmenu: Menu")
+            .expect("Failed to load translations");
+        y10n.add_language_from_str("de", "greeting: moin")
+            .expect("Failed to load translations");
+        let langs: Vec<Language> = vec!["de".into(), "en".into()];
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &langs));
+
+        y10n.add_language_from_str("de", "greeting: servus")
+            .expect("Failed to load translations");
+        assert_eq!(Some("servus"), y10n.lookup("greeting", &langs));
+
+        assert!(matches!(
+            y10n.add_language_from_str("de", "greeting: [broken"),
+            Err(Error::Yaml(_))
+        ));
+        assert_eq!(Some("servus"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+    }
+
+    #[test]
+    fn from_str_normalizes_keys() {
+        let mut y10n = Y10n::from_str("EN-us", "greeting: howdy").expect("Failed to load");
+        y10n.add_language_from_str("EN-gb", "greeting: hiya")
+            .expect("Failed to load translations");
+        let mut languages = y10n.languages();
+        languages.sort();
+        assert_eq!(languages, vec!["en-GB", "en-US"]);
+        assert_eq!(
+            Some("howdy"),
+            y10n.lookup("greeting", &parse_accept_language("en-US"))
+        );
+        assert_eq!(
+            Some("hiya"),
+            y10n.lookup("greeting", &parse_accept_language("en-GB"))
+        );
+
+        y10n.set_default_language("EN-gb");
+        y10n.set_fallback_only("en-us");
+        let negotiated = y10n.negotiate_languages(&parse_accept_language("en-US, fr"));
+        assert_eq!(negotiated.len(), 1);
+        assert_eq!(negotiated[0].to_string(), "en-GB");
+    }
+
+    #[test]
+    fn from_reader() {
+        let file = File::open("l10n/de.yml").expect("Failed to open");
+        let y10n = Y10n::from_reader("de", file).expect("Failed to load translations");
+        assert_eq!(y10n.languages(), vec!["de"]);
+        assert_eq!(Some("moin moin"), y10n.lookup("greeting", &["de".into()]));
+    }
+
+    #[test]
+    fn from_glob_malformed() {
+        match Y10n::from_glob("fixtures/broken/*.yml") {
+            Err(Error::Yaml(_)) => {}
+            other => panic!("Expected a YAML error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn from_glob_lenient_skips_broken() {
+        let (y10n, failures) = Y10n::from_glob_lenient("fixtures/broken/*.yml");
+        assert_eq!(y10n.languages(), vec!["en"]);
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, PathBuf::from("fixtures/broken/de.yml"));
+        assert!(matches!(failures[0].1, Error::Yaml(_)));
+    }
+
+    #[test]
+    fn from_glob_lenient_invalid_pattern() {
+        let (y10n, failures) = Y10n::from_glob_lenient("fixtures/[");
+        assert!(y10n.languages().is_empty());
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(failures[0].1, Error::Pattern { .. }));
+    }
+
+    #[test]
+    fn reload_language_from_disk() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let en = dir.path().join("en.yml");
+        let de = dir.path().join("de.yml");
+        std::fs::write(&en, "greeting: hello").unwrap();
+        std::fs::write(&de, "greeting: moin").unwrap();
+        let pattern = dir.path().join("*.yml");
+        let mut y10n = Y10n::from_glob(&pattern.to_string_lossy()).expect("Failed to load");
+        let de_langs: Vec<Language> = vec!["de".into()];
+
+        std::fs::write(&de, "greeting: moin moin").unwrap();
+        std::fs::write(&en, "greeting: howdy").unwrap();
+        y10n.reload_language("de", &de).expect("Failed to reload");
+        assert_eq!(Some("moin moin"), y10n.lookup("greeting", &de_langs));
+        // Only the reloaded language changes
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+
+        std::fs::write(&de, "greeting: [broken").unwrap();
+        assert!(matches!(
+            y10n.reload_language("de", &de),
+            Err(Error::Yaml(_))
+        ));
+        assert_eq!(Some("moin moin"), y10n.lookup("greeting", &de_langs));
+
+        assert!(matches!(
+            y10n.reload_language("de", &dir.path().join("missing.yml")),
+            Err(Error::Io(_))
+        ));
+        assert_eq!(Some("moin moin"), y10n.lookup("greeting", &de_langs));
+    }
+
+    #[test]
+    fn reload_language_normalizes_key() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let path = dir.path().join("en-US.yml");
+        std::fs::write(&path, "greeting: howdy").unwrap();
+        let pattern = dir.path().join("*.yml");
+        let mut y10n = Y10n::from_glob(&pattern.to_string_lossy()).expect("Failed to load");
+
+        std::fs::write(&path, "greeting: hey y'all").unwrap();
+        y10n.reload_language("EN-us", &path)
+            .expect("Failed to reload");
+        assert_eq!(y10n.languages(), vec!["en-US"]);
+        assert_eq!(
+            Some("hey y'all"),
+            y10n.lookup("greeting", &parse_accept_language("en-US"))
+        );
+    }
+
+    #[test]
+    fn load_crlf_preserved() {
+        let y10n = Y10n::from_glob("fixtures/crlf/*.yml").expect("Failed to load translations");
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(
+            Some("line one\r\nline two"),
+            y10n.lookup("multiline", &langs)
+        );
+    }
+
+    #[test]
+    fn load_crlf_normalized() {
+        let options = LoadOptions {
+            line_endings: LineEndings::Lf,
+            ..Default::default()
+        };
+        let y10n = Y10n::from_glob_with_options("fixtures/crlf/*.yml", &options)
+            .expect("Failed to load translations");
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("line one\nline two"), y10n.lookup("multiline", &langs));
+        assert_eq!(Some("one\ntwo\n"), y10n.lookup("block", &langs));
+    }
+
+    #[test]
+    fn translate_missing_key_handler() {
+        let mut y10n = y10n_from(&[("en", "greeting: hello")]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(None, y10n.translate("edit_button", &langs));
+
+        y10n.set_missing_key_handler(|key| {
+            let mut humanized = key.replace('_', " ");
+            humanized[..1].make_ascii_uppercase();
+            Some(humanized)
+        });
+        assert_eq!(Some("hello".into()), y10n.translate("greeting", &langs));
+        assert_eq!(
+            Some("Edit button".into()),
+            y10n.translate("edit_button", &langs)
+        );
+    }
+
+    #[test]
+    fn humanize() {
+        assert_eq!("Edit Button", humanize_key("user_profile.edit_button"));
+        assert_eq!("Edit Button", humanize_key("user_profile.editButton"));
+        assert_eq!("Greeting", humanize_key("greeting"));
+    }
+
+    #[test]
+    fn translate_humanized() {
+        let mut y10n = y10n_from(&[("en", "greeting: hello")]);
+        let langs: Vec<Language> = vec!["en".into()];
+        y10n.set_humanize_missing_keys(true);
+        assert_eq!(Some("hello".into()), y10n.translate("greeting", &langs));
+        assert_eq!(
+            Some("Edit Button".into()),
+            y10n.translate("user_profile.edit_button", &langs)
+        );
+    }
+
+    #[test]
+    fn lookup_whitespace_preserved() {
+        let y10n = y10n_from(&[("en", "fragment: '  and then '")]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("  and then "), y10n.lookup("fragment", &langs));
+    }
+
+    #[test]
+    fn lookup_whitespace_trimmed() {
+        let mut y10n = y10n_from(&[("en", "fragment: '  and then '")]);
+        y10n.set_whitespace(Whitespace::Trim);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("and then"), y10n.lookup("fragment", &langs));
+    }
+
+    #[test]
+    fn validate_plurals_gaps() {
+        let y10n = y10n_from(&[
+            (
+                "ru",
+                "files:\n  one: '{{count}} файл'\n  many: '{{count}} файлов'\n  other: '{{count}} файла'\nmenu:\n  open: 'Открыть'",
+            ),
+            ("en", "files:\n  one: '{{count}} file'\n  other: '{{count}} files'"),
+        ]);
+
+        let gaps = y10n.validate_plurals();
+        assert_eq!(
+            gaps,
+            vec![PluralGap {
+                language: "ru".into(),
+                key: "files".into(),
+                missing: vec![plural::PluralCategory::Few],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_wildcard() {
+        let languages = parse_accept_language("en;q=0.8,*;q=0.1");
+        assert_eq!(languages.len(), 2);
+        assert!(languages[1].is_wildcard());
+        assert_eq!(languages[1].quality, 0.1);
+        assert_eq!(languages[1].to_string(), "*;q=0.1");
+
+        let languages = parse_accept_language_strict("*, de;q=0.5, *;q=2");
+        assert_eq!(languages.len(), 2);
+        assert!(languages[0].is_wildcard());
+    }
+
+    #[test]
+    fn wildcard_default_language() {
+        let mut y10n = y10n_from(&[
+            ("de", "greeting: moin\nsecret: pfannkuchen"),
+            ("en", "greeting: hello\nfarewell: bye"),
+        ]);
+        let langs = parse_accept_language("*");
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("moin"), y10n.localize(&langs)["greeting"].as_str());
+
+        y10n.set_default_language("en");
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &langs));
+        assert_eq!(
+            y10n.negotiate("fr, *;q=0.1")
+                .iter()
+                .map(|l| l.key())
+                .collect::<Vec<String>>(),
+            vec!["en"]
+        );
+
+        let langs = parse_accept_language("de;q=0.8,*;q=0.1");
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("bye"), y10n.lookup("farewell", &langs));
+    }
+
+    #[test]
+    fn configured_default_language() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "_config:\n  default: en\ngreeting: hello\nsecret: pancakes",
+            ),
+            ("de", "greeting: moin"),
+        ]);
+        let langs: Vec<Language> = vec!["fr".into()];
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &langs));
+
+        let langs: Vec<Language> = vec!["de".into()];
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &langs));
+        let localized = y10n.localize(&langs);
+        assert_eq!(Some("pancakes"), localized["secret"].as_str());
+        assert!(localized.get(CONFIG_KEY).is_none());
+    }
+
+    #[test]
+    fn render_with_nested_context() {
+        let y10n = y10n_from(&[(
+            "en",
+            "welcome: 'Welcome {{user.name}}, you have {{count}} messages'",
+        )]);
+        let langs: Vec<Language> = vec!["en".into()];
+        let context = serde_json::json!({"user": {"name": "Ferris"}, "count": 3});
+        assert_eq!(
+            Some("Welcome Ferris, you have 3 messages".to_string()),
+            y10n.render_with("welcome", &langs, &context)
+        );
+        assert_eq!(None, y10n.render_with("missing", &langs, &context));
+    }
+
+    #[test]
+    fn explain_json_consulted() {
+        let y10n = y10n_from(&[("en", "greeting: hello"), ("de", "farewell: tschüss")]);
+        let langs: Vec<Language> = vec!["fr".into(), "de".into(), "en".into()];
+        let explained = y10n.explain_json("greeting", &langs);
+
+        assert_eq!(
+            explained,
+            serde_json::json!({
+                "key": "greeting",
+                "resolved": {"language": "en", "value": "hello"},
+                "consulted": [
+                    {"language": "fr", "loaded": false, "found": false},
+                    {"language": "de", "loaded": true, "found": false},
+                    {"language": "en", "loaded": true, "found": true, "value": "hello"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn explain_json_nested() {
+        let y10n = y10n_from(&[
+            ("en", "menu:\n  file:\n    open: Open"),
+            ("de", "menu:\n  edit: Bearbeiten"),
+        ]);
+        let langs: Vec<Language> = vec!["de".into(), "en".into()];
+        let explained = y10n.explain_json("menu.file.open", &langs);
+
+        assert_eq!(
+            Some("Open"),
+            y10n.lookup("menu.file.open", &langs),
+            "explain_json must agree with lookup"
+        );
+        assert_eq!(
+            explained,
+            serde_json::json!({
+                "key": "menu.file.open",
+                "resolved": {"language": "en", "value": "Open"},
+                "consulted": [
+                    {"language": "de", "loaded": true, "found": false},
+                    {"language": "en", "loaded": true, "found": true, "value": "Open"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn render_with_preformatted() {
+        let y10n = y10n_from(&[("de", "total: 'Summe: {{amount}} ({{date}})'")]);
+        let langs: Vec<Language> = vec!["de".into()];
+        let context = serde_json::json!({"amount": "1.234,56 €", "date": "{{15.10.2026}}"});
+        assert_eq!(
+            Some("Summe: 1.234,56 € ({{15.10.2026}})".to_string()),
+            y10n.render_with("total", &langs, &context)
+        );
+    }
+
+    #[test]
+    fn pluralize_categories() {
+        let y10n = y10n_from(&[("en", "items:\n  one: 'One item'\n  other: 'Many items'")]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(
+            Some("Many items"),
+            y10n.pluralize("items", 0, &langs).as_deref()
+        );
+        assert_eq!(
+            Some("One item"),
+            y10n.pluralize("items", 1, &langs).as_deref()
+        );
+        assert_eq!(
+            Some("Many items"),
+            y10n.pluralize("items", 42, &langs).as_deref()
+        );
+        assert_eq!(None, y10n.pluralize("missing", 1, &langs).as_deref());
+    }
+
+    #[test]
+    fn pluralize_repeated() {
+        let y10n = y10n_from(&[
+            (
+                "ru-RU",
+                "items:\n  one: 'one'\n  few: 'few'\n  many: 'many'\n  other: 'other'",
+            ),
+            ("en", "items:\n  one: 'one'\n  other: 'other'"),
+        ]);
+        let ru = parse_accept_language("ru-RU");
+        let en: Vec<Language> = vec!["en".into()];
+
+        for _ in 0..3 {
+            assert_eq!(Some("one"), y10n.pluralize("items", 21, &ru).as_deref());
+            assert_eq!(Some("few"), y10n.pluralize("items", 3, &ru).as_deref());
+            assert_eq!(Some("many"), y10n.pluralize("items", 11, &ru).as_deref());
+            assert_eq!(Some("other"), y10n.pluralize("items", 21, &en).as_deref());
+            assert_eq!(Some("one"), y10n.pluralize("items", 1, &en).as_deref());
+        }
+    }
+
+    #[test]
+    fn pluralize_icu() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "items: '{count, plural, one {# item} other {# items}}'",
+            ),
+            (
+                "de",
+                "items: '{count, plural, =0 {Keine Artikel} one {# Artikel} other {# Artikel}}'",
+            ),
+            (
+                "fr",
+                "items: '{count, plural, one {# article} other {# articles}}'",
+            ),
+        ]);
+        let pluralize = |count, language: &str| {
+            y10n.pluralize("items", count, &[language.into()])
+                .map(|s| s.into_owned())
+        };
+
+        assert_eq!(pluralize(0, "en").as_deref(), Some("0 items"));
+        assert_eq!(pluralize(1, "en").as_deref(), Some("1 item"));
+        assert_eq!(pluralize(1234, "en").as_deref(), Some("1234 items"));
+        assert_eq!(pluralize(0, "de").as_deref(), Some("Keine Artikel"));
+        assert_eq!(pluralize(1, "de").as_deref(), Some("1 Artikel"));
+        assert_eq!(pluralize(0, "fr").as_deref(), Some("0 article"));
+        assert_eq!(pluralize(2, "fr").as_deref(), Some("2 articles"));
+    }
+
+    #[test]
+    fn select_gender() {
+        let y10n = y10n_from(&[(
+            "en",
+            "replied: '{gender, select, male {He} female {She} other {They}} replied'",
+        )]);
+        let select = |gender: Option<&str>| {
+            let mut args = HashMap::new();
+            if let Some(gender) = gender {
+                args.insert("gender".to_string(), gender.to_string());
+            }
+            y10n.select("replied", &["en".into()], &args)
+        };
+
+        assert_eq!(select(Some("male")).as_deref(), Some("He replied"));
+        assert_eq!(select(Some("female")).as_deref(), Some("She replied"));
+        assert_eq!(select(Some("other")).as_deref(), Some("They replied"));
+        assert_eq!(select(Some("robot")).as_deref(), Some("They replied"));
+        assert_eq!(select(None).as_deref(), Some("They replied"));
+        assert_eq!(
+            y10n.select("missing", &["en".into()], &HashMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn pluralize_custom_rule() {
+        fn everything_is_one(_: u64) -> plural::PluralCategory {
+            plural::PluralCategory::One
         }
+
+        let mut y10n = y10n_from(&[("xx", "items:\n  one: 'one'\n  other: 'other'")]);
+        let langs: Vec<Language> = vec!["xx".into()];
+        assert_eq!(Some("other"), y10n.pluralize("items", 5, &langs).as_deref());
+
+        y10n.set_plural_rule("XX", everything_is_one);
+        assert_eq!(Some("one"), y10n.pluralize("items", 5, &langs).as_deref());
     }
-}
 
-impl From<&str> for Language {
-    fn from(code: &str) -> Self {
-        Self {
-            code: code.to_string(),
-            region: None,
-            quality: 1.0,
+    #[test]
+    fn pluralize_ranges() {
+        let y10n = y10n_from(&[(
+            "en",
+            "items:\n  '0': 'No items'\n  one: 'One item'\n  '2-5': 'A few items'\n  '6+': 'Lots of items'\n  other: 'Some items'",
+        )]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(
+            Some("No items"),
+            y10n.pluralize("items", 0, &langs).as_deref()
+        );
+        assert_eq!(
+            Some("One item"),
+            y10n.pluralize("items", 1, &langs).as_deref()
+        );
+        assert_eq!(
+            Some("A few items"),
+            y10n.pluralize("items", 3, &langs).as_deref()
+        );
+        assert_eq!(
+            Some("Lots of items"),
+            y10n.pluralize("items", 10, &langs).as_deref()
+        );
+    }
+
+    #[test]
+    fn load_duplicate_keys_warn() {
+        let y10n =
+            Y10n::from_glob("fixtures/duplicates/*.yml").expect("Failed to load translations");
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("goodbye"), y10n.lookup("greeting", &langs));
+    }
+
+    #[test]
+    fn load_duplicate_keys_deny() {
+        let options = LoadOptions {
+            duplicate_keys: DuplicateKeys::Deny,
+            ..Default::default()
+        };
+        match Y10n::from_glob_with_options("fixtures/duplicates/*.yml", &options) {
+            Err(Error::DuplicateKeys(keys)) => assert_eq!(keys, vec!["greeting"]),
+            other => panic!("Expected duplicate keys to be detected, got {:?}", other),
         }
     }
-}
 
-#[derive(Clone, Debug)]
-enum Error {
-    Generic,
-}
+    #[test]
+    fn from_sources_yaml() {
+        let en: &[u8] = b"greeting: 'hello world'\nsecret: 'pancakes'";
+        let de: &[u8] = b"greeting: 'moin moin'";
+        let y10n =
+            Y10n::from_sources(&[("en", en), ("de", de)], Format::Yaml).expect("Failed to load");
+        assert_eq!(y10n.languages().len(), 2);
 
-/**
- * Merge a couple of serde_yaml together
- *
- * THis code courtesy of https://stackoverflow.com/a/67743348
- */
-fn merge_yaml(a: &mut serde_yaml::Value, b: serde_yaml::Value) {
-    match (a, b) {
-        (a @ &mut serde_yaml::Value::Mapping(_), serde_yaml::Value::Mapping(b)) => {
-            let a = a.as_mapping_mut().unwrap();
-            for (k, v) in b {
-                if v.is_sequence() && a.contains_key(&k) && a[&k].is_sequence() {
-                    let mut _b = a.get(&k).unwrap().as_sequence().unwrap().to_owned();
-                    _b.append(&mut v.as_sequence().unwrap().to_owned());
-                    a[&k] = serde_yaml::Value::from(_b);
-                    continue;
-                }
-                if !a.contains_key(&k) {
-                    a.insert(k.to_owned(), v.to_owned());
-                } else {
-                    merge_yaml(&mut a[&k], v);
-                }
-            }
+        let langs: Vec<Language> = vec!["de".into(), "en".into()];
+        assert_eq!(Some("moin moin"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("pancakes"), y10n.lookup("secret", &langs));
+    }
+
+    #[test]
+    fn from_sources_invalid() {
+        let en: &[u8] = b"greeting: [unterminated";
+        assert!(Y10n::from_sources(&[("en", en)], Format::Yaml).is_err());
+    }
+
+    #[test]
+    fn all_placeholders_by_key() {
+        let y10n = y10n_from(&[
+            ("en", "greeting: 'Hello there {{who}}'\nsecret: 'pancakes'\nmenu:\n  user: '{{user.name}}'"),
+            ("de", "greeting: 'Hallo {{who}} von {{team}}'"),
+        ]);
+        let placeholders = y10n.all_placeholders();
+
+        let names = |key: &str| {
+            let mut names: Vec<&String> = placeholders[key].iter().collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names("greeting"), vec!["team", "who"]);
+        assert_eq!(names("menu.user"), vec!["user.name"]);
+        assert!(names("secret").is_empty());
+    }
+
+    #[test]
+    fn expansion_ratio_longer() {
+        let y10n = y10n_from(&[
+            ("en", "save: 'Save'\nmenu:\n  open: 'Open'\nonly_en: 'Only'"),
+            ("de", "save: 'Speichern'\nmenu:\n  open: 'Öffnen'"),
+        ]);
+        let ratio = y10n.expansion_ratio("en", "de");
+        assert!(ratio > 1.0);
+        assert!((ratio - (9.0 / 4.0 + 6.0 / 4.0) / 2.0).abs() < f64::EPSILON);
+        assert_eq!(0.0, y10n.expansion_ratio("en", "fr"));
+    }
+
+    #[test]
+    fn localize_regional_chains() {
+        let y10n = y10n_from(&[
+            ("fr", "greeting: bonjour\nfarewell: au revoir"),
+            (
+                "de",
+                "greeting: moin\nfarewell: tschüss\nsecret: pfannkuchen",
+            ),
+            ("de-AT", "greeting: servus"),
+        ]);
+        let langs: Vec<Language> = vec![
+            Language::from("fr-CA").unwrap(),
+            Language::from("de-AT").unwrap(),
+        ];
+        let localized = y10n.localize(&langs);
+        assert_eq!(Some("bonjour"), localized["greeting"].as_str());
+        assert_eq!(Some("au revoir"), localized["farewell"].as_str());
+        assert_eq!(Some("pfannkuchen"), localized["secret"].as_str());
+
+        let langs: Vec<Language> = vec![Language::from("de-AT").unwrap()];
+        let localized = y10n.localize(&langs);
+        assert_eq!(Some("servus"), localized["greeting"].as_str());
+        assert_eq!(Some("tschüss"), localized["farewell"].as_str());
+    }
+
+    #[test]
+    fn localize_regional_files() {
+        let y10n = Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        assert!(y10n.languages().contains(&&"en-US".to_string()));
+
+        let localized = y10n.localize(&parse_accept_language("en-US"));
+        assert_eq!(Some("soccer"), localized["football"].as_str());
+        assert_eq!(Some("hello"), localized["greeting"].as_str());
+
+        let localized = y10n.localize(&parse_accept_language("en-GB"));
+        assert_eq!(Some("colour"), localized["color"].as_str());
+        assert_eq!(Some("hello"), localized["greeting"].as_str());
+
+        let localized = y10n.localize(&parse_accept_language("en"));
+        assert_eq!(Some("color"), localized["color"].as_str());
+        assert!(localized.get("football").is_none());
+    }
+
+    #[test]
+    fn localize_cached_reuse() {
+        let y10n = y10n_from(&[("en", "greeting: hello"), ("de", "greeting: moin")]);
+        let langs: Vec<Language> = vec!["de".into(), "en".into()];
+        let first = y10n.localize_cached(&langs);
+        let second = y10n.localize_cached(&langs);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(y10n.localize(&langs), *first);
+
+        y10n.clear_cache();
+        assert_eq!(0, y10n.cache.len());
+        let third = y10n.localize_cached(&langs);
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(first, third);
+    }
+
+    #[test]
+    fn localize_cached_eviction() {
+        let mut y10n = y10n_from(&[("en", "greeting: hello"), ("de", "greeting: moin")]);
+        y10n.set_cache_capacity(2);
+        let en: Vec<Language> = vec!["en".into()];
+        let de: Vec<Language> = vec!["de".into()];
+        let both: Vec<Language> = vec!["de".into(), "en".into()];
+
+        let first = y10n.localize_cached(&en);
+        y10n.localize_cached(&de);
+        y10n.localize_cached(&both);
+        assert_eq!(2, y10n.cache.len());
+
+        let again = y10n.localize_cached(&en);
+        assert!(!Arc::ptr_eq(&first, &again));
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn dump_sorted() {
+        let first = y10n_from(&[("en", "b: 2\na: 1\nmenu:\n  open: Open\n  close: Close")]);
+        let second = y10n_from(&[("en", "menu:\n  close: Close\n  open: Open\na: 1\nb: 2")]);
+        let langs: Vec<Language> = vec!["en".into()];
+
+        let dump = first.dump(&langs).expect("Failed to dump");
+        assert_eq!(dump, second.dump(&langs).expect("Failed to dump"));
+        assert!(dump.find("a: 1").unwrap() < dump.find("b: 2").unwrap());
+    }
+
+    #[test]
+    fn layered_priority() {
+        let tenant = y10n_from(&[("en", "title: 'Acme'")]);
+        let theme = y10n_from(&[
+            ("en", "title: 'Themed'\nbutton: 'Go'\nnav: [theme]"),
+            ("de", "button: 'Los'"),
+        ]);
+        let base = y10n_from(&[(
+            "en",
+            "title: 'Base'\nbutton: 'Submit'\nfooter: 'Bye'\nnav: [base]",
+        )]);
+
+        let y10n = Y10n::layered(vec![tenant, theme, base]);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("Acme"), y10n.lookup("title", &langs));
+        assert_eq!(Some("Go"), y10n.lookup("button", &langs));
+        assert_eq!(Some("Bye"), y10n.lookup("footer", &langs));
+        assert_eq!(Some("Los"), y10n.lookup("button", &["de".into()]));
+
+        let nav: Vec<&str> = y10n.translations["en"]["nav"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert_eq!(nav, vec!["theme"]);
+    }
+
+    #[test]
+    fn layered_concat_sequences() {
+        let mut theme = y10n_from(&[("en", "nav: [theme]")]);
+        theme.set_merge_strategy(MergeStrategy::ConcatSequences);
+        let base = y10n_from(&[("en", "nav: [base]")]);
+
+        let y10n = Y10n::layered(vec![theme, base]);
+        let nav = y10n.localize(&["en".into()])["nav"].clone();
+        assert_eq!(
+            nav,
+            serde_yaml::from_str::<serde_yaml::Value>("[base, theme]").unwrap()
+        );
+    }
+
+    #[test]
+    fn localize_merge_strategies() {
+        let mut y10n = y10n_from(&[
+            ("de", "menu:\n  items: [Datei, Bearbeiten]"),
+            ("en", "menu:\n  items: [File, Edit, View]\n  title: Menu"),
+        ]);
+        let langs: Vec<Language> = vec!["de".into(), "en".into()];
+        let items = |y10n: &Y10n| -> Vec<String> {
+            y10n.localize(&langs)["menu"]["items"]
+                .as_sequence()
+                .unwrap()
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        };
+
+        assert_eq!(items(&y10n), vec!["Datei", "Bearbeiten"]);
+        assert_eq!(
+            y10n.localize(&langs)["menu"]["title"].as_str(),
+            Some("Menu")
+        );
+
+        y10n.set_merge_strategy(MergeStrategy::ConcatSequences);
+        assert_eq!(
+            items(&y10n),
+            vec!["File", "Edit", "View", "Datei", "Bearbeiten"]
+        );
+    }
+
+    #[test]
+    fn localize_strips_reserved_keys() {
+        let y10n = Y10n::from_pairs(vec![
+            ("en", "greeting: 'Mention @{user} to notify them'"),
+            (
+                "de",
+                "_config:\n  default: de\n_format:\n  decimal_separator: ','\n_extends: en",
+            ),
+        ])
+        .expect("Literal @{..} text must load");
+        let localized = y10n.localize(&parse_accept_language("de,en"));
+        assert!(localized.get(CONFIG_KEY).is_none());
+        assert!(localized.get(FORMAT_KEY).is_none());
+        // Only _config and _format are reserved, other keys are ordinary strings
+        assert_eq!(Some("en"), localized["_extends"].as_str());
+        assert_eq!(
+            Some("Mention @{user} to notify them"),
+            localized["greeting"].as_str()
+        );
+    }
+
+    #[test]
+    fn replace_all_translations() {
+        let mut y10n = y10n_from(&[
+            ("en", "greeting: hello\nsecret: pancakes"),
+            ("de", "greeting: moin"),
+        ]);
+        y10n.set_whitespace(Whitespace::Trim);
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(
+            Some("pancakes"),
+            y10n.localize_cached(&langs)["secret"].as_str()
+        );
+
+        y10n.replace_all(y10n_from(&[("en", "greeting: ' howdy '\nfarewell: bye")]));
+        assert_eq!(None, y10n.lookup("secret", &langs));
+        assert_eq!(Some("howdy"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("bye"), y10n.lookup("farewell", &langs));
+        assert_eq!(None, y10n.lookup("greeting", &["de".into()]));
+        assert!(y10n.localize_cached(&langs).get("secret").is_none());
+    }
+
+    #[test]
+    fn load_unknown_tags_stripped() {
+        let y10n = Y10n::from_glob("fixtures/tags/*.yml").expect("Failed to load translations");
+        let langs: Vec<Language> = vec!["en".into()];
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("pancakes"), y10n.lookup("secret", &langs));
+    }
+
+    #[test]
+    fn load_unknown_tags_deny() {
+        let options = LoadOptions {
+            unknown_tags: UnknownTags::Deny,
+            ..Default::default()
+        };
+        match Y10n::from_glob_with_options("fixtures/tags/*.yml", &options) {
+            Err(Error::UnknownTags(tags)) => assert_eq!(tags, vec!["!encrypted (line 4)"]),
+            other => panic!("Expected unknown tags to be detected, got {:?}", other),
         }
-        (a, b) => *a = b,
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn strict_regions() {
+        let mut y10n =
+            Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        let languages = parse_accept_language("en-AU, en-GB;q=0.5");
+        assert_eq!(
+            y10n.try_localize(&languages).expect("Failed to localize")["color"].as_str(),
+            Some("color")
+        );
+
+        y10n.set_strict_regions(true);
+        match y10n.try_localize(&languages) {
+            Err(Error::MissingRegion(key)) => assert_eq!(key, "en-AU"),
+            other => panic!("Expected a missing region, got {:?}", other),
+        }
+
+        let languages = parse_accept_language("en-GB, en");
+        assert_eq!(
+            y10n.try_localize(&languages).expect("Failed to localize")["color"].as_str(),
+            Some("colour")
+        );
+    }
 
     #[test]
-    fn test_language_from_str() {
-        let language: Language = "en".into();
-        assert_eq!(language.code, "en");
+    fn size_report() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        let report = y10n.size_report();
+        assert_eq!(report.get("en"), Some(&(3, 47)));
+        assert_eq!(report.len(), 2);
     }
 
     #[test]
-    fn y10n_from_valid_glob() {
-        let y10n = Y10n::from_glob("l10n/*.yml");
-        assert_eq!(y10n.languages().len(), 2);
+    fn lookup_short_circuits() {
+        let y10n = y10n_from(&[
+            ("de", "greeting: 'moin'\nmenu:\n  open: 'Öffnen'"),
+            ("en", "greeting: 'hello'\nfarewell: 'bye'\nmenu: 'Menu'"),
+            ("fr", "secret: 'crêpes'"),
+        ]);
+        let langs: Vec<Language> = vec!["de".into(), "en".into(), "fr".into()];
+
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("bye"), y10n.lookup("farewell", &langs));
+        assert_eq!(Some("crêpes"), y10n.lookup("secret", &langs));
+        assert_eq!(None, y10n.lookup("menu", &langs));
     }
 
     #[test]
-    fn y10n_localize() {
+    fn lookup_dotted_path() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "menu:\n  file:\n    open: 'Open'\n    close.all: 'Close all'\n  edit: 'Edit'\nmenu.edit: 'Flat edit'",
+            ),
+            ("de", "menu:\n  file:\n    open: 'Öffnen'"),
+        ]);
+        let langs: Vec<Language> = vec!["de".into(), "en".into()];
+
+        assert_eq!(Some("Öffnen"), y10n.lookup("menu.file.open", &langs));
+        assert_eq!(
+            Some("Close all"),
+            y10n.lookup("menu.file.close.all", &langs)
+        );
+        assert_eq!(Some("Flat edit"), y10n.lookup("menu.edit", &langs));
+        assert_eq!(None, y10n.lookup("menu.file", &langs));
+        assert_eq!(None, y10n.lookup("menu.view.open", &langs));
+        assert_eq!(None, y10n.lookup("menu.file.open.more", &langs));
+        assert_eq!(None, y10n.lookup("menu..open", &langs));
+    }
+
+    #[test]
+    fn missing_keys_nested() {
+        let y10n = Y10n::from_glob("fixtures/partial/*.yml").expect("Failed to load translations");
+        let missing = y10n.missing_keys("en");
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            missing["de"],
+            vec!["cart.items", "menu.file.close", "menu.help"]
+        );
+        assert!(y10n.missing_keys("fr").is_empty());
+    }
+
+    #[test]
+    fn extra_keys_nested() {
+        let y10n = Y10n::from_glob("fixtures/partial/*.yml").expect("Failed to load translations");
+        let extra = y10n.extra_keys("en");
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra["de"], vec!["menu.file.obsolete"]);
+    }
+
+    #[test]
+    fn verify_flawed() {
+        let y10n = y10n_from(&[
+            ("en", "items:\n  one: '{{count}} item'\n  other: 'items'"),
+            (
+                "ru",
+                "items:\n  one: '{{count}} штука'\n  other: '{{count}} штук'",
+            ),
+        ]);
+
+        let errors = y10n.verify("de").expect_err("Expected validation errors");
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], ValidationError::MissingReference("de".into()));
+        assert_eq!(
+            errors[1].to_string(),
+            "ru: items is missing the plural categories: few, many"
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "en: items.other is missing the placeholders: count"
+        );
+    }
+
+    #[test]
+    fn stats_coverage() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "greeting: hello\nfarewell: bye\nmenu:\n  open: Open\n  close: Close\nitems:\n  one: item\n  other: items",
+            ),
+            ("de", "greeting: hallo\nmenu:\n  open: Öffnen\nobsolete: veraltet"),
+            ("de-AT", "greeting: servus\nmenu:\n  close: Schließen"),
+        ]);
+        let stats = y10n.stats("en");
+        assert_eq!(stats.base, "en");
+        assert_eq!(stats.base_keys, 5);
+
+        assert_eq!(
+            stats.languages["en"],
+            LanguageStats {
+                keys: 5,
+                translated: 5,
+                coverage: 100.0,
+            }
+        );
+        assert_eq!(
+            stats.languages["de"],
+            LanguageStats {
+                keys: 3,
+                translated: 2,
+                coverage: 40.0,
+            }
+        );
+        assert_eq!(stats.languages["de-AT"].keys, 2);
+        assert_eq!(stats.languages["de-AT"].translated, 3);
+        assert_eq!(stats.languages["de-AT"].coverage, 60.0);
+
+        let json = serde_json::to_value(&stats).expect("Failed to serialize");
+        assert_eq!(json["languages"]["de"]["coverage"], 40.0);
+    }
+
+    #[test]
+    fn stats_half_covered() {
+        let y10n = y10n_from(&[("en", "a: 1\nb: 2\nc: 3\nd: 4"), ("de", "a: eins\nb: zwei")]);
+        let stats = y10n.stats("en");
+        assert_eq!(stats.languages["de"].keys, 2);
+        assert_eq!(stats.languages["de"].coverage, 50.0);
+        assert_eq!(stats.languages["en"].keys, 4);
+
+        let missing = y10n.stats("fr");
+        assert_eq!(missing.base_keys, 0);
+        assert_eq!(missing.languages["de"].coverage, 100.0);
+    }
+
+    #[test]
+    fn entries_flattened() {
+        let y10n = Y10n::from_glob("fixtures/partial/*.yml").expect("Failed to load translations");
+        let entries: Vec<(String, Option<&str>)> = y10n
+            .entries("en")
+            .map(|(key, value)| (key, value.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("menu.file.open".to_string(), Some("Open")),
+                ("menu.file.close".to_string(), Some("Close")),
+                ("menu.help".to_string(), Some("Help")),
+                ("cart.items.one".to_string(), Some("{{count}} item")),
+                ("cart.items.other".to_string(), Some("{{count}} items")),
+                ("greeting".to_string(), Some("Hello")),
+            ]
+        );
+
+        let values = Y10n::from_glob("fixtures/values/*.yml").expect("Failed to load");
+        let thresholds = values
+            .entries("en")
+            .find(|(key, _)| key == "thresholds")
+            .map(|(_, value)| value.as_sequence().map(|seq| seq.len()));
+        assert_eq!(thresholds, Some(Some(2)));
+
+        assert_eq!(y10n.entries("fr").count(), 0);
+    }
+
+    #[test]
+    fn verify_valid() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "greeting: 'Hello {{who}}'\nitems:\n  one: 'one'\n  other: 'other'",
+            ),
+            (
+                "de",
+                "greeting: 'Hallo {{who}}'\nitems:\n  one: 'eins'\n  other: 'andere'",
+            ),
+        ]);
+        assert_eq!(y10n.verify("en"), Ok(()));
+    }
+
+    #[test]
+    fn verify_missing_keys() {
+        let y10n = Y10n::from_glob("l10n/*.yml").expect("Failed to load translations");
+        assert_eq!(
+            y10n.verify("en").expect_err("Expected missing keys")[0].to_string(),
+            "de is missing the keys: secret, thankyou"
+        );
+    }
+
+    #[test]
+    fn placeholders_skip_reserved_keys() {
+        let y10n = y10n_from(&[
+            ("en", "_config:\n  note: '{{who}}'\ngreeting: 'Hi {{who}}'"),
+            (
+                "de",
+                "_config:\n  note: '{{wer}}'\ngreeting: 'Moin {{who}}'",
+            ),
+        ]);
+        assert!(y10n.validate_placeholders("en").is_empty());
+        assert!(y10n.validate_placeholders("de").is_empty());
+    }
+
+    #[test]
+    fn placeholders_across_languages() {
+        let y10n =
+            Y10n::from_glob("fixtures/placeholders/*.yml").expect("Failed to load translations");
+        let mismatches = y10n.validate_placeholders("en");
+        assert_eq!(
+            mismatches,
+            vec![
+                PlaceholderMismatch {
+                    language: "de".into(),
+                    key: "greeting".into(),
+                    missing: vec!["who".into()],
+                    extra: vec![],
+                },
+                PlaceholderMismatch {
+                    language: "fr".into(),
+                    key: "account.balance".into(),
+                    missing: vec!["amount".into()],
+                    extra: vec!["montant".into()],
+                },
+            ]
+        );
+        assert_eq!(
+            ValidationError::PlaceholderMismatch(mismatches[1].clone()).to_string(),
+            "fr: account.balance is missing the placeholders: amount and has unknown placeholders: montant"
+        );
+        assert!(y10n.validate_placeholders("ja").is_empty());
+
+        let errors = y10n.verify("en").expect_err("Expected validation errors");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "de: greeting is missing the placeholders: who"
+        );
+    }
+
+    #[test]
+    fn branch_placeholders() {
+        let y10n = Y10n::from_glob("fixtures/branches/*.yml").expect("Failed to load translations");
+        assert_eq!(
+            y10n.validate_branch_placeholders(),
+            vec![
+                BranchMismatch {
+                    language: "en".into(),
+                    key: "action".into(),
+                    branch: "mobile".into(),
+                    missing: vec!["what".into()],
+                },
+                BranchMismatch {
+                    language: "en".into(),
+                    key: "cart.items".into(),
+                    branch: "other".into(),
+                    missing: vec!["count".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn max_depth_merge() {
+        let mut y10n =
+            Y10n::from_glob("fixtures/nested/*.yml").expect("Failed to load translations");
+        let languages: Vec<Language> = vec!["de".into(), "en".into()];
+        let deepest = |value: &serde_yaml::Value| {
+            let mut value = value.clone();
+            for key in ["a", "b", "c", "d", "e", "f", "g", "h"].iter() {
+                value = value[*key].clone();
+            }
+            value
+        };
+
+        let merged = deepest(&y10n.localize(&languages));
+        assert_eq!(merged["shared"].as_str(), Some("de"));
+        assert_eq!(merged["en_only"].as_str(), Some("en"));
+
+        y10n.set_max_depth(4);
+        let truncated = deepest(&y10n.localize(&languages));
+        assert_eq!(truncated["shared"].as_str(), Some("de"));
+        assert!(truncated.get("en_only").is_none());
+    }
+
+    #[test]
+    fn max_depth_visit() {
+        let mut y10n =
+            Y10n::from_glob("fixtures/nested/*.yml").expect("Failed to load translations");
+        assert!(y10n
+            .all_placeholders()
+            .contains_key("a.b.c.d.e.f.g.h.shared"));
+
+        y10n.set_max_depth(4);
+        let placeholders = y10n.all_placeholders();
+        assert!(!placeholders.contains_key("a.b.c.d.e.f.g.h.shared"));
+        assert!(placeholders.contains_key("a.shallow"));
+    }
+
+    #[test]
+    fn lookup_raw_values() {
         use serde_yaml::Value;
 
-        let y10n = Y10n::from_glob("l10n/*.yml");
-        let en = Language::from("en").expect("Failed to parse!");
-        let de = Language::from("de").expect("Failed to parse!");
-        let value = y10n.localize(&[de, en]);
-        if let Some(map) = value.as_mapping() {
-            let key = "greeting".into();
-            let greeting = map.get(&key).expect("Failed to find a greeting");
-            assert_eq!(&Value::String("moin moin".to_string()), greeting);
+        let y10n = Y10n::from_glob("fixtures/values/*.yml").expect("Failed to load translations");
+        let de: Vec<Language> = vec!["de".into(), "en".into()];
+        let en: Vec<Language> = vec!["en".into()];
 
-            let secret = map.get(&"secret".into()).expect("Failed to find a secret");
-            assert_eq!(&Value::String("pancakes".to_string()), secret);
-        } else {
-            assert!(false, "The value wasn't a map like I expected");
-        }
+        assert_eq!(
+            Some(&Value::from(2)),
+            y10n.lookup_value("decimal_places", &de)
+        );
+        assert_eq!(
+            Some(&Value::from(0)),
+            y10n.lookup_value("decimal_places", &en)
+        );
+        assert_eq!(
+            Some(&Value::from("€")),
+            y10n.lookup_value("currency_symbol", &de)
+        );
+        assert_eq!(Some(&Value::from(true)), y10n.lookup_value("show_vat", &de));
+        assert!(y10n
+            .lookup_value("thresholds", &de)
+            .is_some_and(|v| v.is_sequence()));
+        assert_eq!(None, y10n.lookup_value("missing", &de));
     }
 
     #[test]
-    fn language_from_segment() {
-        let lang = Language::from("en-US");
-        assert!(lang.is_ok());
-        let lang = lang.unwrap();
-        assert_eq!("en", lang.code);
-        assert_eq!(Some("US".to_string()), lang.region);
-        assert_eq!(1.0, lang.quality);
+    fn lookup_typed_values() {
+        let y10n = y10n_from(&[
+            (
+                "en",
+                "show_vat: false\nmax_items: 10\nrate: 0.5\ngreeting: 'hello'",
+            ),
+            ("de", "show_vat: true\nmax_items: 5"),
+        ]);
+        let de: Vec<Language> = vec!["de".into(), "en".into()];
+        let en: Vec<Language> = vec!["en".into()];
+
+        assert_eq!(Some(true), y10n.lookup_bool("show_vat", &de));
+        assert_eq!(Some(false), y10n.lookup_bool("show_vat", &en));
+        assert_eq!(Some(5), y10n.lookup_i64("max_items", &de));
+        assert_eq!(Some(10), y10n.lookup_i64("max_items", &en));
+        assert_eq!(Some(0.5), y10n.lookup_f64("rate", &de));
+        assert_eq!(Some(10.0), y10n.lookup_f64("max_items", &en));
     }
 
     #[test]
-    fn parse_langs_simple() {
-        let header = "en-US,en;q=0.5";
-        let langs = parse_accept_language(&header);
-        assert_eq!(langs.len(), 2);
+    fn lookup_typed_values_mismatch() {
+        let y10n = y10n_from(&[("en", "greeting: 'hello'\nflag: 'true'\nrate: 0.5")]);
+        let en: Vec<Language> = vec!["en".into()];
+
+        assert_eq!(None, y10n.lookup_bool("greeting", &en));
+        assert_eq!(None, y10n.lookup_bool("flag", &en));
+        assert_eq!(None, y10n.lookup_i64("rate", &en));
+        assert_eq!(None, y10n.lookup_f64("greeting", &en));
+        assert_eq!(None, y10n.lookup_i64("missing", &en));
     }
 
     #[test]
-    fn parse_langs_multi() {
-        let header = "en-US,en;q=0.7,de-DE;q=0.3";
-        let langs = parse_accept_language(&header);
-        assert_eq!(langs.len(), 3);
-        let de = langs.get(2).unwrap();
-        assert_eq!("de", de.code);
-        assert_eq!(0.3, de.quality);
+    fn lookup_with_ambient_languages() {
+        let y10n = y10n_from(&[("en", "greeting: 'hello'"), ("de", "greeting: 'moin'")]);
+        assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+
+        with_languages(&["de".into()], || {
+            assert_eq!(Some("moin"), y10n.lookup("greeting", &[]));
+            assert_eq!(Some("hello"), y10n.lookup("greeting", &["en".into()]));
+
+            with_languages(&["en".into()], || {
+                assert_eq!(Some("hello"), y10n.lookup("greeting", &[]));
+            });
+            assert_eq!(Some("moin"), y10n.lookup("greeting", &[]));
+        });
+
+        assert!(current_languages().is_empty());
+        assert_eq!(None, y10n.lookup("greeting", &[]));
+    }
+
+    #[test]
+    fn regions_for_code() {
+        let y10n = Y10n::from_glob("fixtures/regions/*.yml").expect("Failed to load translations");
+        assert_eq!(vec!["GB", "US"], y10n.regions_for("en"));
+        assert!(y10n.regions_for("de").is_empty());
+    }
+
+    #[test]
+    fn negotiate_only_loaded() {
+        let y10n = y10n_from(&[("en", "greeting: hello"), ("de", "greeting: moin")]);
+        let langs = y10n.negotiate("fr;q=0.9,de;q=0.5,ja,en-US;q=0.7,en;q=0.6");
+        let keys: Vec<String> = langs.iter().map(|l| l.key()).collect();
+        assert_eq!(keys, vec!["en", "de"]);
+        assert_eq!(0.7, langs[0].quality);
+    }
+
+    #[test]
+    fn negotiate_prefers_regional_file() {
+        let y10n = y10n_from(&[("en", "greeting: hello"), ("en-GB", "greeting: hiya")]);
+        let langs = y10n.negotiate("en-GB,en-US;q=0.8");
+        let keys: Vec<String> = langs.iter().map(|l| l.key()).collect();
+        assert_eq!(keys, vec!["en-GB", "en"]);
+    }
+
+    #[test]
+    fn fallback_only_language() {
+        let mut y10n = y10n_from(&[
+            ("en", "greeting: hello\nsecret: pancakes"),
+            ("de", "greeting: moin"),
+        ]);
+        y10n.set_fallback_only("en");
+
+        let best = y10n
+            .best_match("en-US,en;q=0.9,de;q=0.5")
+            .expect("No best match");
+        assert_eq!("de", best.key());
+        assert_eq!(1, y10n.negotiate("en-US,en;q=0.9,de;q=0.5").len());
+
+        let langs = vec![best];
+        assert_eq!(Some("moin"), y10n.lookup("greeting", &langs));
+        assert_eq!(Some("pancakes"), y10n.lookup("secret", &langs));
+        assert_eq!(Some("pancakes"), y10n.localize(&langs)["secret"].as_str());
+    }
+
+    #[test]
+    fn negotiate_drops_unacceptable() {
+        let y10n = y10n_from(&[("en", "greeting: hello"), ("de", "greeting: moin")]);
+        let langs = y10n.negotiate("de;q=0,en;q=0.1");
+        let keys: Vec<String> = langs.iter().map(|l| l.key()).collect();
+        assert_eq!(keys, vec!["en"]);
     }
 }
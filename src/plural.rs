@@ -0,0 +1,501 @@
+//!
+//! Plural rules for selecting the right form of a localized string for a count
+//!
+//! The rules here are a subset of the [CLDR plural
+//! rules](https://unicode-org.github.io/cldr-staging/charts/latest/supplemental/language_plural_rules.html)
+//! covering integer counts.
+//!
+
+use serde_yaml::{Mapping, Value};
+
+lazy_static! {
+    /// The start of an ICU plural argument, e.g. `{count, plural,`
+    static ref ICU_PLURAL: regex::Regex =
+        regex::Regex::new(r"\{\s*\w+\s*,\s*plural\s*,").unwrap();
+    /// The start of an ICU select argument, e.g. `{gender, select,`
+    static ref ICU_SELECT: regex::Regex =
+        regex::Regex::new(r"\{\s*(?P<arg>\w+)\s*,\s*select\s*,").unwrap();
+}
+
+/**
+ * The CLDR plural categories
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    /// Used for zero in languages such as Arabic
+    Zero,
+    /// The singular form in most languages
+    One,
+    /// The dual form in languages such as Arabic
+    Two,
+    /// Small counts in languages such as Russian or Polish
+    Few,
+    /// Large counts in languages such as Russian or Polish
+    Many,
+    /// The general plural form, which every language has
+    Other,
+}
+
+impl PluralCategory {
+    /**
+     * Parse the name of a category as it would be written as a key in a translation file
+     */
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "zero" => Some(PluralCategory::Zero),
+            "one" => Some(PluralCategory::One),
+            "two" => Some(PluralCategory::Two),
+            "few" => Some(PluralCategory::Few),
+            "many" => Some(PluralCategory::Many),
+            "other" => Some(PluralCategory::Other),
+            _ => None,
+        }
+    }
+
+    /**
+     * The name of the category as it would be written as a key in a translation file
+     */
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/**
+ * A function which maps a count onto its plural category
+ */
+pub type PluralRule = fn(u64) -> PluralCategory;
+
+/**
+ * Return the plural rule for the given language code
+ *
+ * Languages without a known rule use the English rule of `one` for 1 and `other` for everything
+ * else, which is also correct for most Germanic and Romance languages.
+ *
+ * Rules are plain functions chosen by a static `match`, so resolving one on every call is cheap
+ * and safe to share between threads without any caching.
+ */
+pub fn rule_for(code: &str) -> PluralRule {
+    rules(code).0
+}
+
+/**
+ * Return the plural categories which translations in the given language code must provide
+ */
+pub fn required_categories(code: &str) -> &'static [PluralCategory] {
+    rules(code).1
+}
+
+/**
+ * Return the plural category of the count for the given language code
+ */
+pub fn category(code: &str, count: u64) -> PluralCategory {
+    rule_for(code)(count)
+}
+
+fn rules(code: &str) -> (PluralRule, &'static [PluralCategory]) {
+    use PluralCategory::*;
+
+    match code {
+        "fr" | "pt" => (french, &[One, Other]),
+        "ru" | "uk" | "be" => (russian, &[One, Few, Many, Other]),
+        "pl" => (polish, &[One, Few, Many, Other]),
+        "cs" | "sk" => (czech, &[One, Few, Other]),
+        "ar" => (arabic, &[Zero, One, Two, Few, Many, Other]),
+        "ja" | "ko" | "zh" | "th" | "vi" | "id" => (none, &[Other]),
+        _ => (english, &[One, Other]),
+    }
+}
+
+fn english(n: u64) -> PluralCategory {
+    match n {
+        1 => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+fn french(n: u64) -> PluralCategory {
+    match n {
+        0 | 1 => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+fn russian(n: u64) -> PluralCategory {
+    match (n % 10, n % 100) {
+        (1, r) if r != 11 => PluralCategory::One,
+        (2..=4, r) if !(12..=14).contains(&r) => PluralCategory::Few,
+        _ => PluralCategory::Many,
+    }
+}
+
+fn polish(n: u64) -> PluralCategory {
+    match (n, n % 10, n % 100) {
+        (1, _, _) => PluralCategory::One,
+        (_, 2..=4, r) if !(12..=14).contains(&r) => PluralCategory::Few,
+        _ => PluralCategory::Many,
+    }
+}
+
+fn czech(n: u64) -> PluralCategory {
+    match n {
+        1 => PluralCategory::One,
+        2..=4 => PluralCategory::Few,
+        _ => PluralCategory::Other,
+    }
+}
+
+fn arabic(n: u64) -> PluralCategory {
+    match (n, n % 100) {
+        (0, _) => PluralCategory::Zero,
+        (1, _) => PluralCategory::One,
+        (2, _) => PluralCategory::Two,
+        (_, 3..=10) => PluralCategory::Few,
+        (_, 11..=99) => PluralCategory::Many,
+        _ => PluralCategory::Other,
+    }
+}
+
+fn none(_: u64) -> PluralCategory {
+    PluralCategory::Other
+}
+
+/**
+ * Check whether an explicit count key such as `"0"`, `"2-5"` or `"6+"` covers the count
+ */
+fn range_matches(key: &str, count: u64) -> bool {
+    let key = key.trim();
+
+    if let Some(start) = key.strip_suffix('+') {
+        return start
+            .trim()
+            .parse::<u64>()
+            .is_ok_and(|start| count >= start);
+    }
+
+    if let Some((start, end)) = key.split_once('-') {
+        return match (start.trim().parse::<u64>(), end.trim().parse::<u64>()) {
+            (Ok(start), Ok(end)) => (start..=end).contains(&count),
+            _ => false,
+        };
+    }
+
+    key.parse::<u64>().is_ok_and(|exact| exact == count)
+}
+
+/**
+ * Check whether a mapping looks like a set of plural branches, where every key is either a plural
+ * category or an explicit count
+ */
+pub fn is_plural(branches: &Mapping) -> bool {
+    !branches.is_empty()
+        && branches.iter().all(|(key, _)| match key.as_str() {
+            Some(key) => PluralCategory::parse(key).is_some() || is_explicit(key),
+            None => key.is_u64(),
+        })
+}
+
+/**
+ * Check whether a key is an explicit count or range such as `"0"`, `"2-5"` or `"6+"`
+ */
+fn is_explicit(key: &str) -> bool {
+    key.trim()
+        .trim_end_matches('+')
+        .split('-')
+        .all(|part| part.trim().parse::<u64>().is_ok())
+}
+
+/**
+ * Return the required categories for the language which are missing from the plural branches
+ *
+ * Branches which use explicit counts are considered to be deliberately covering the counts they
+ * need, and only the categories are checked.
+ */
+pub fn missing_categories(branches: &Mapping, code: &str) -> Vec<PluralCategory> {
+    required_categories(code)
+        .iter()
+        .filter(|category| !branches.contains_key(&Value::String(category.as_str().to_string())))
+        .copied()
+        .collect()
+}
+
+/**
+ * Select the branch of a plural mapping for the count
+ *
+ * Explicit count and range keys take precedence, followed by the CLDR category for the language
+ * and finally the `other` branch.
+ */
+pub fn select<'a>(branches: &'a Mapping, code: &str, count: u64) -> Option<&'a Value> {
+    select_with_rule(branches, rule_for(code), count)
+}
+
+/**
+ * Select the branch for the count just like `select`, using an already resolved plural rule
+ */
+pub fn select_with_rule(branches: &Mapping, rule: PluralRule, count: u64) -> Option<&Value> {
+    for (key, value) in branches.iter() {
+        if let Some(key) = key.as_str() {
+            if range_matches(key, count) {
+                return Some(value);
+            }
+        } else if key.as_u64() == Some(count) {
+            return Some(value);
+        }
+    }
+
+    let category = rule(count);
+    branches
+        .get(&Value::String(category.as_str().to_string()))
+        .or_else(|| branches.get(&Value::String("other".to_string())))
+}
+
+/**
+ * Check whether a string contains an ICU plural argument such as
+ * `{count, plural, one {# item} other {# items}}`
+ */
+pub fn is_icu(message: &str) -> bool {
+    ICU_PLURAL.is_match(message)
+}
+
+/**
+ * Format the ICU plural arguments of the message for the count, replacing each argument with its
+ * selected branch in which `#` is replaced by the count
+ *
+ * Exact selectors such as `=0` take precedence over the plural categories, and the `other` branch
+ * is used when the category of the count has no branch. Text around the arguments is kept as-is,
+ * so `"You have {n, plural, one {# item} other {# items}}"` with a count of 2 becomes
+ * `"You have 2 items"`. The `offset:` extension isn't supported.
+ *
+ * Returns `None` when an argument is malformed or has no branch for the count.
+ */
+pub fn format_icu(message: &str, rule: PluralRule, count: u64) -> Option<String> {
+    let exact = format!("={}", count);
+    let category = rule(count).as_str();
+    let mut formatted = String::new();
+    let mut rest = message;
+
+    while let Some(start) = ICU_PLURAL.find(rest) {
+        formatted.push_str(&rest[..start.start()]);
+        let (branches, len) = icu_branches(&rest[start.end()..])?;
+        let (_, branch) = branches
+            .iter()
+            .find(|(selector, _)| *selector == exact)
+            .or_else(|| branches.iter().find(|(selector, _)| *selector == category))
+            .or_else(|| branches.iter().find(|(selector, _)| *selector == "other"))?;
+        formatted.push_str(&branch.replace('#', &count.to_string()));
+        rest = &rest[start.end() + len..];
+    }
+    formatted.push_str(rest);
+    Some(formatted)
+}
+
+/**
+ * Check whether a string contains an ICU select argument such as
+ * `{gender, select, male {He} female {She} other {They}}`
+ */
+pub fn is_icu_select(message: &str) -> bool {
+    ICU_SELECT.is_match(message)
+}
+
+/**
+ * Format the ICU select arguments of the message, replacing each argument with the branch
+ * matching the value `resolve` returns for its name
+ *
+ * The `other` branch is used when the value has no branch of its own or `resolve` has no value
+ * for the argument, so `"{gender, select, male {He} female {She} other {They}} replied"` becomes
+ * `"They replied"` for a gender of `unknown`.
+ *
+ * Returns `None` when an argument is malformed or has neither a matching nor an `other` branch.
+ */
+pub fn format_icu_select(
+    message: &str,
+    resolve: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let mut formatted = String::new();
+    let mut rest = message;
+
+    while let Some(captures) = ICU_SELECT.captures(rest) {
+        let (start, arg) = (captures.get(0)?, &captures["arg"]);
+        formatted.push_str(&rest[..start.start()]);
+        let (branches, len) = icu_branches(&rest[start.end()..])?;
+        let value = resolve(arg);
+        let (_, branch) = branches
+            .iter()
+            .find(|(selector, _)| Some(*selector) == value.as_deref())
+            .or_else(|| branches.iter().find(|(selector, _)| *selector == "other"))?;
+        formatted.push_str(branch);
+        rest = &rest[start.end() + len..];
+    }
+    formatted.push_str(rest);
+    Some(formatted)
+}
+
+/**
+ * Parse the branches of an ICU plural or select argument following its `{count, plural,` prefix, such as
+ * `one {# item} other {# items}}`, returning them along with the length up to and including the
+ * closing brace of the argument
+ */
+fn icu_branches(source: &str) -> Option<(Vec<(&str, &str)>, usize)> {
+    let mut branches = vec![];
+    let mut pos = 0;
+
+    loop {
+        pos += source[pos..].len() - source[pos..].trim_start().len();
+        if source[pos..].starts_with('}') {
+            return Some((branches, pos + 1));
+        }
+
+        let len = source[pos..].find(|c: char| c.is_whitespace() || c == '{')?;
+        if len == 0 {
+            return None;
+        }
+        let selector = &source[pos..pos + len];
+        pos += len;
+        pos += source[pos..].len() - source[pos..].trim_start().len();
+        if !source[pos..].starts_with('{') {
+            return None;
+        }
+
+        let start = pos + 1;
+        let mut depth = 1;
+        let end = source[start..].char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                Some(start + i)
+            } else {
+                None
+            }
+        })?;
+        branches.push((selector, &source[start..end]));
+        pos = end + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icu_plurals() {
+        let message = "{count, plural, one {# item} other {# items}}";
+        assert!(is_icu(message));
+        assert_eq!(format_icu(message, english, 0).as_deref(), Some("0 items"));
+        assert_eq!(format_icu(message, english, 1).as_deref(), Some("1 item"));
+        assert_eq!(
+            format_icu(message, english, 1000000).as_deref(),
+            Some("1000000 items")
+        );
+        assert_eq!(format_icu(message, french, 0).as_deref(), Some("0 item"));
+    }
+
+    #[test]
+    fn icu_exact_and_surrounding_text() {
+        let message =
+            "Cart: {n,plural,=0{empty} one{# item} other{# items}}, {m, plural, other {# saved}}";
+        assert_eq!(
+            format_icu(message, english, 0).as_deref(),
+            Some("Cart: empty, 0 saved")
+        );
+        assert_eq!(
+            format_icu(message, english, 3).as_deref(),
+            Some("Cart: 3 items, 3 saved")
+        );
+    }
+
+    #[test]
+    fn icu_malformed() {
+        assert!(!is_icu("{count} items"));
+        assert_eq!(format_icu("{n, plural, one {# item}", english, 1), None);
+        assert_eq!(format_icu("{n, plural, one {# item}}", english, 2), None);
+        assert_eq!(format_icu("{n, plural, {# item}}", english, 1), None);
+    }
+
+    #[test]
+    fn icu_select() {
+        let message = "{gender, select, male {He} female {She} other {They}} replied";
+        let gender = |value: &'static str| move |_: &str| Some(value.to_string());
+        assert!(is_icu_select(message));
+        assert!(!is_icu(message));
+        assert_eq!(
+            format_icu_select(message, gender("male")).as_deref(),
+            Some("He replied")
+        );
+        assert_eq!(
+            format_icu_select(message, gender("female")).as_deref(),
+            Some("She replied")
+        );
+        assert_eq!(
+            format_icu_select(message, gender("other")).as_deref(),
+            Some("They replied")
+        );
+        assert_eq!(
+            format_icu_select(message, gender("robot")).as_deref(),
+            Some("They replied")
+        );
+        assert_eq!(
+            format_icu_select(message, |_| None).as_deref(),
+            Some("They replied")
+        );
+        assert_eq!(
+            format_icu_select("{g, select, male {He}}", gender("female")),
+            None
+        );
+    }
+
+    #[test]
+    fn english_rules() {
+        assert_eq!(PluralCategory::Other, category("en", 0));
+        assert_eq!(PluralCategory::One, category("en", 1));
+        assert_eq!(PluralCategory::Other, category("en", 2));
+    }
+
+    #[test]
+    fn french_rules() {
+        assert_eq!(PluralCategory::One, category("fr", 0));
+        assert_eq!(PluralCategory::One, category("fr", 1));
+        assert_eq!(PluralCategory::Other, category("fr", 2));
+    }
+
+    #[test]
+    fn russian_rules() {
+        assert_eq!(PluralCategory::One, category("ru", 21));
+        assert_eq!(PluralCategory::Few, category("ru", 3));
+        assert_eq!(PluralCategory::Many, category("ru", 12));
+        assert_eq!(PluralCategory::Many, category("ru", 5));
+    }
+
+    #[test]
+    fn plural_shapes() {
+        let plural: Mapping = serde_yaml::from_str("one: a\nother: b\n'2-5': c").unwrap();
+        assert!(is_plural(&plural));
+        let section: Mapping = serde_yaml::from_str("one: a\nopen: b").unwrap();
+        assert!(!is_plural(&section));
+    }
+
+    #[test]
+    fn missing_russian() {
+        let plural: Mapping = serde_yaml::from_str("one: a\nmany: b\nother: c").unwrap();
+        assert_eq!(vec![PluralCategory::Few], missing_categories(&plural, "ru"));
+        assert!(missing_categories(&plural, "en").is_empty());
+    }
+
+    #[test]
+    fn ranges() {
+        assert!(range_matches("0", 0));
+        assert!(range_matches("2-5", 2));
+        assert!(range_matches("2-5", 5));
+        assert!(!range_matches("2-5", 6));
+        assert!(range_matches("6+", 10));
+        assert!(!range_matches("other", 10));
+    }
+}
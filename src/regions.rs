@@ -0,0 +1,88 @@
+//!
+//! Localized display names for region codes, e.g. `US` as "United States"
+//!
+//! The built-in table covers a set of commonly used regions in English and German.
+//!
+
+use crate::Language;
+
+/// Region codes with their English and German display names
+const REGIONS: &[(&str, &str, &str)] = &[
+    ("AT", "Austria", "Österreich"),
+    ("AU", "Australia", "Australien"),
+    ("BE", "Belgium", "Belgien"),
+    ("BR", "Brazil", "Brasilien"),
+    ("CA", "Canada", "Kanada"),
+    ("CH", "Switzerland", "Schweiz"),
+    ("CN", "China", "China"),
+    ("DE", "Germany", "Deutschland"),
+    ("DK", "Denmark", "Dänemark"),
+    ("ES", "Spain", "Spanien"),
+    ("FI", "Finland", "Finnland"),
+    ("FR", "France", "Frankreich"),
+    ("GB", "United Kingdom", "Vereinigtes Königreich"),
+    ("IE", "Ireland", "Irland"),
+    ("IN", "India", "Indien"),
+    ("IT", "Italy", "Italien"),
+    ("JP", "Japan", "Japan"),
+    ("KR", "South Korea", "Südkorea"),
+    ("MX", "Mexico", "Mexiko"),
+    ("NL", "Netherlands", "Niederlande"),
+    ("NO", "Norway", "Norwegen"),
+    ("NZ", "New Zealand", "Neuseeland"),
+    ("PL", "Poland", "Polen"),
+    ("PT", "Portugal", "Portugal"),
+    ("RU", "Russia", "Russland"),
+    ("SE", "Sweden", "Schweden"),
+    ("TW", "Taiwan", "Taiwan"),
+    ("UA", "Ukraine", "Ukraine"),
+    ("US", "United States", "Vereinigte Staaten"),
+    ("ZA", "South Africa", "Südafrika"),
+];
+
+/**
+ * Return the display name of the region code in the first supported language
+ *
+ * Region codes are matched case-insensitively. Returns `None` when the region is unknown or none
+ * of the languages are supported.
+ */
+pub fn region_name(region: &str, languages: &[Language]) -> Option<&'static str> {
+    let (_, en, de) = REGIONS
+        .iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(region))?;
+
+    languages.iter().find_map(|lang| match lang.code.as_str() {
+        "en" => Some(*en),
+        "de" => Some(*de),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_names() {
+        assert_eq!(Some("United States"), region_name("US", &["en".into()]));
+        assert_eq!(Some("United Kingdom"), region_name("gb", &["en".into()]));
+    }
+
+    #[test]
+    fn german_names() {
+        assert_eq!(
+            Some("Vereinigte Staaten"),
+            region_name("US", &["de".into()])
+        );
+        assert_eq!(
+            Some("Deutschland"),
+            region_name("DE", &["fr".into(), "de".into(), "en".into()])
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(None, region_name("XX", &["en".into()]));
+        assert_eq!(None, region_name("US", &["fr".into()]));
+    }
+}
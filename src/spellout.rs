@@ -0,0 +1,158 @@
+//!
+//! Rendering numbers as words, e.g. 1234 as "one thousand two hundred thirty-four"
+//!
+//! Only English is supported to start with, other languages return `None` so that callers can
+//! fall back to a numeric rendering.
+//!
+
+use crate::Language;
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [&str; 7] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+/**
+ * Spell out the number in words using the rules of the first supported language
+ *
+ * Returns `None` when none of the languages are supported.
+ */
+pub fn spellout(number: i64, languages: &[Language]) -> Option<String> {
+    languages.iter().find_map(|lang| match lang.code.as_str() {
+        "en" => Some(english(number)),
+        _ => None,
+    })
+}
+
+/**
+ * Spell out a number below one thousand in English
+ */
+fn english_hundreds(n: u64) -> String {
+    let mut words = vec![];
+    let hundreds = n / 100;
+    let rest = n % 100;
+
+    if hundreds > 0 {
+        words.push(format!("{} hundred", ONES[hundreds as usize]));
+    }
+
+    if rest >= 20 {
+        match rest % 10 {
+            0 => words.push(TENS[(rest / 10) as usize].to_string()),
+            ones => words.push(format!(
+                "{}-{}",
+                TENS[(rest / 10) as usize],
+                ONES[ones as usize]
+            )),
+        }
+    } else if rest > 0 {
+        words.push(ONES[rest as usize].to_string());
+    }
+    words.join(" ")
+}
+
+fn english(number: i64) -> String {
+    if number == 0 {
+        return ONES[0].to_string();
+    }
+
+    let mut n = number.unsigned_abs();
+    let mut groups = vec![];
+    let mut scale = 0;
+
+    while n > 0 {
+        let group = n % 1000;
+        if group > 0 {
+            let words = english_hundreds(group);
+            groups.push(match SCALES[scale] {
+                "" => words,
+                scale => format!("{} {}", words, scale),
+            });
+        }
+        n /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+
+    let words = groups.join(" ");
+    if number < 0 {
+        format!("minus {}", words)
+    } else {
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn en(number: i64) -> String {
+        spellout(number, &["en".into()]).expect("Failed to spell out")
+    }
+
+    #[test]
+    fn small_numbers() {
+        assert_eq!("zero", en(0));
+        assert_eq!("seven", en(7));
+        assert_eq!("thirteen", en(13));
+        assert_eq!("forty", en(40));
+        assert_eq!("forty-two", en(42));
+        assert_eq!("one hundred", en(100));
+        assert_eq!("minus five", en(-5));
+    }
+
+    #[test]
+    fn large_numbers() {
+        assert_eq!("one thousand two hundred thirty-four", en(1234));
+        assert_eq!("one million five", en(1_000_005));
+        assert_eq!(
+            "nine quintillion two hundred twenty-three quadrillion three hundred seventy-two trillion thirty-six billion eight hundred fifty-four million seven hundred seventy-five thousand eight hundred seven",
+            en(i64::MAX)
+        );
+        assert_eq!(
+            "minus nine quintillion two hundred twenty-three quadrillion three hundred seventy-two trillion thirty-six billion eight hundred fifty-four million seven hundred seventy-five thousand eight hundred eight",
+            en(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(None, spellout(1, &["de".into()]));
+        assert_eq!(
+            Some("one".to_string()),
+            spellout(1, &["de".into(), "en".into()])
+        );
+    }
+}
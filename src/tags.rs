@@ -0,0 +1,52 @@
+//!
+//! Detection of YAML tags which aren't part of the core schema, such as `!something`
+//!
+//! serde_yaml ignores such tags when deserializing, which silently discards whatever meaning the
+//! author intended them to have.
+//!
+
+use yaml_rust::scanner::{Scanner, TokenType};
+
+/// The tags of the YAML core schema, which are understood when deserializing
+const CORE: &[&str] = &[
+    "str", "int", "float", "bool", "null", "seq", "map", "binary",
+];
+
+/**
+ * Return every tag in the document which isn't part of the core schema, along with its line
+ *
+ * Documents which fail to scan report the tags found up until the failure, leaving the error to be
+ * reported when the document is deserialized.
+ */
+pub(crate) fn find_unknown(source: &str) -> Vec<String> {
+    Scanner::new(source.chars())
+        .filter_map(|token| match token.1 {
+            TokenType::Tag(handle, suffix) => {
+                if handle == "!!" && CORE.contains(&suffix.as_str()) {
+                    None
+                } else {
+                    Some(format!("{}{} (line {})", handle, suffix, token.0.line()))
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn core_tags() {
+        assert!(find_unknown("a: !!str 1\nb: !!int 2\nc: plain").is_empty());
+    }
+
+    #[test]
+    fn unknown_tags() {
+        assert_eq!(
+            find_unknown("a: !!str 1\nb: !secret value\nc: !!python/object {}"),
+            vec!["!secret (line 2)", "!!python/object (line 3)"]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+/// The tera module has the optional Tera support for Y10n which can be enabled
+/// with the `tera` feature
+use crate::interpolate;
+use log::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub use crate::{Language, Y10n};
+pub use tera::{Tera, Value};
+
+///  This function ensures that the `t` function inside Tera templates can be used
+///  for resolving Y10n values, just like the `t` helper for Handlebars
+///
+///  Tera functions only take keyword arguments, so the localization key is given
+///  with the `key` argument and every other argument is interpolated into the
+///  localization string's `{{name}}` placeholders.
+///
+///  Suppose that the `en.yml` file contains:
+///
+///  ```yaml
+///  ---
+///  thankyou: "Thanks for playing {{team}}!"
+///  ```
+///
+///  And then the Tera template of: `{{ t(key="thankyou", team=user) }}`
+///
+///  This will look up the `thankyou` string and interpolate the current context's
+///  `user` value into the `team` placeholder. When a `count` argument is given and
+///  the localization string has plural forms, the form for the count is selected
+///  (see `Y10n::pluralize`) before interpolating.
+///
+///  The function can be registered with:
+///
+///  ```rust
+///  use std::sync::Arc;
+///  use y10n::tera::*;
+///  let y10n = Arc::new(Y10n::from_glob("l10n/*.yml").unwrap());
+///  let mut tera = Tera::default();
+///  tera.register_function("t", TeraFunction::new(y10n, vec!["en".into()]));
+///  ```
+///
+///  Tera requires registered functions to be `'static`, so the Y10n is shared
+///  with an `Arc`. Keys which none of the languages have render as an empty
+///  string.
+#[derive(Clone, Debug)]
+pub struct TeraFunction {
+    y10n: Arc<Y10n>,
+    languages: Vec<Language>,
+}
+
+impl TeraFunction {
+    ///
+    /// Instantiation of the TeraFunction should come with a shared Y10n struct and
+    /// an array of preferred languages for rendering the localization strings in
+    /// the Tera templates
+    pub fn new(y10n: Arc<Y10n>, languages: Vec<Language>) -> Self {
+        Self { y10n, languages }
+    }
+}
+
+impl tera::Function for TeraFunction {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let key = args
+            .get("key")
+            .and_then(|key| key.as_str())
+            .ok_or_else(|| tera::Error::msg("The t function requires a `key` argument"))?;
+        trace!("Looking up localization string: {}", key);
+
+        let count = args.get("count").and_then(|count| count.as_u64());
+        let translation = match count {
+            Some(count) => self
+                .y10n
+                .pluralize(key, count, &self.languages)
+                .or_else(|| self.y10n.translate(key, &self.languages)),
+            None => self.y10n.translate(key, &self.languages),
+        };
+
+        let context = Value::Object(
+            args.iter()
+                .filter(|(name, _)| *name != "key")
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        );
+        let rendered = translation
+            .map(|translation| {
+                interpolate::interpolate(&translation, &|name| {
+                    interpolate::resolve_json(&context, name)
+                })
+            })
+            .unwrap_or_default();
+        Ok(Value::String(rendered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tera::Context;
+
+    #[test]
+    fn test_tera_function() {
+        let y10n = Arc::new(Y10n::from_glob("l10n/*.yml").expect("Failed to load translations"));
+        let template = r#"Well that's it. {{ t(key="thankyou", team=team) }}"#;
+
+        let mut tera = Tera::default();
+        tera.register_function("t", TeraFunction::new(y10n, vec!["en".into()]));
+        let mut context = Context::new();
+        context.insert("team", "Foo");
+        let rendered = tera
+            .render_str(template, &context)
+            .expect("Failed to render");
+
+        assert_eq!(rendered, "Well that's it. Thanks for playing Foo!");
+    }
+
+    #[test]
+    fn test_plurals() {
+        let y10n = Arc::new(
+            Y10n::from_pairs(vec![(
+                "en",
+                "items: '{count, plural, one {# item} other {# items}} for {{who}}'",
+            )])
+            .expect("Failed to load translations"),
+        );
+        let mut tera = Tera::default();
+        tera.register_function("t", TeraFunction::new(y10n, vec!["en".into()]));
+
+        let rendered = tera
+            .render_str(
+                r#"{{ t(key="items", count=1, who="you") }}, {{ t(key="items", count=3, who="us") }}"#,
+                &Context::new(),
+            )
+            .expect("Failed to render");
+        assert_eq!(rendered, "1 item for you, 3 items for us");
+    }
+
+    #[test]
+    fn test_missing_key_argument() {
+        let y10n = Arc::new(Y10n::from_glob("l10n/*.yml").expect("Failed to load translations"));
+        let mut tera = Tera::default();
+        tera.register_function("t", TeraFunction::new(y10n, vec!["en".into()]));
+
+        assert!(tera
+            .render_str(r#"{{ t(name="x") }}"#, &Context::new())
+            .is_err());
+        assert_eq!(
+            tera.render_str(r#"{{ t(key="missing") }}"#, &Context::new())
+                .expect("Failed to render"),
+            ""
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//!
+//! Hot-reloading of translation files as they change on disk, enabled with the `watch` feature
+//!
+
+use crate::{normalize_key, Error, LoadOptions, Y10n};
+use log::*;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// The handle for a running watcher returned by `Y10n::watch`
+///
+/// The watcher runs until the handle is dropped.
+pub struct WatcherHandle {
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl std::fmt::Debug for WatcherHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WatcherHandle")
+    }
+}
+
+/// Return the leading directories of the pattern which contain no glob syntax, which is the
+/// directory which needs to be watched
+fn root(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|c| {
+            !c.as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect()
+}
+
+impl Y10n {
+    /// Load the translations from the given glob and watch them for changes, reloading each file's
+    /// language into the returned shared `Y10n` as it is modified
+    ///
+    /// Only the language of the changed file is reloaded, from every file in the glob which shares
+    /// its stem so that languages split across directories are merged again just like when they
+    /// were loaded. If any of those files fail to load the error is logged and the previously
+    /// loaded translations for that language are kept.
+    ///
+    /// The watcher stops once the returned `WatcherHandle` is dropped, after which the shared
+    /// `Y10n` keeps the translations it had last loaded.
+    ///
+    /// ```rust,no_run
+    /// use y10n::*;
+    /// let (y10n, _handle) = Y10n::watch("l10n/*.yml").expect("Failed to watch translations");
+    /// let greeting = y10n.read().unwrap().lookup("greeting", &["en".into()]).map(String::from);
+    /// ```
+    pub fn watch(pattern: &str) -> Result<(Arc<RwLock<Y10n>>, WatcherHandle), Error> {
+        let y10n = Arc::new(RwLock::new(Y10n::from_glob(pattern)?));
+
+        let absolute = std::env::current_dir()?.join(pattern);
+        let matcher =
+            glob::Pattern::new(&absolute.to_string_lossy()).map_err(|source| Error::Pattern {
+                pattern: pattern.to_string(),
+                source,
+            })?;
+
+        let pattern = pattern.to_string();
+        let shared = y10n.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        error!("Failed to watch translations: {}", e);
+                        return;
+                    }
+                };
+
+                if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    return;
+                }
+
+                for path in event.paths.iter().filter(|p| matcher.matches_path(p)) {
+                    let key = match path.file_stem() {
+                        Some(stem) => normalize_key(&stem.to_string_lossy()),
+                        None => continue,
+                    };
+                    debug!(
+                        "Reloading translations for {} after: {}",
+                        key,
+                        path.display()
+                    );
+
+                    // Parse the files before taking the write lock, so lookups aren't blocked
+                    let loader = shared.read().unwrap().loader();
+                    match loader.load_language(&pattern, &key, &LoadOptions::default()) {
+                        Ok(Some(value)) => shared.write().unwrap().replace_language(&key, value),
+                        Ok(None) => {}
+                        Err(e) => error!(
+                            "Failed to reload {}, keeping the previous translations: {}",
+                            key, e
+                        ),
+                    }
+                }
+            })?;
+        watcher.watch(&root(&absolute), RecursiveMode::Recursive)?;
+
+        Ok((y10n, WatcherHandle { _watcher: watcher }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn root_of_pattern() {
+        assert_eq!(
+            PathBuf::from("/srv/l10n"),
+            root(Path::new("/srv/l10n/**/*.yml"))
+        );
+        assert_eq!(PathBuf::from("l10n"), root(Path::new("l10n/*.yml")));
+    }
+
+    /// Poll until the lookup returns the expected value, tolerating slow file notifications
+    fn wait_for(y10n: &Arc<RwLock<Y10n>>, expected: &str) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            let value = y10n
+                .read()
+                .unwrap()
+                .lookup("greeting", &["en".into()])
+                .map(String::from);
+            if value.as_deref() == Some(expected) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    #[test]
+    fn watch_reloads_only_changed_language() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let en = dir.path().join("en.yml");
+        let de = dir.path().join("de.yml");
+        std::fs::write(&en, "greeting: hello").unwrap();
+        std::fs::write(&de, "greeting: moin").unwrap();
+
+        let pattern = dir.path().join("*.yml");
+        let (y10n, _handle) =
+            Y10n::watch(&pattern.to_string_lossy()).expect("Failed to watch translations");
+
+        std::fs::write(&en, "greeting: howdy").unwrap();
+        assert!(wait_for(&y10n, "howdy"));
+        assert_eq!(
+            Some("moin"),
+            y10n.read().unwrap().lookup("greeting", &["de".into()])
+        );
+    }
+
+    #[test]
+    fn watch_reloads_merged_files() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        std::fs::create_dir(dir.path().join("common")).unwrap();
+        std::fs::create_dir(dir.path().join("pages")).unwrap();
+        let pages = dir.path().join("pages").join("en.yml");
+        std::fs::write(dir.path().join("common").join("en.yml"), "farewell: bye").unwrap();
+        std::fs::write(&pages, "greeting: hello").unwrap();
+
+        let pattern = dir.path().join("**").join("*.yml");
+        let (y10n, _handle) =
+            Y10n::watch(&pattern.to_string_lossy()).expect("Failed to watch translations");
+        assert!(wait_for(&y10n, "hello"));
+
+        std::fs::write(&pages, "greeting: howdy").unwrap();
+        assert!(wait_for(&y10n, "howdy"));
+        // The strings of the other file for the language are merged in again
+        assert_eq!(
+            Some("bye"),
+            y10n.read().unwrap().lookup("farewell", &["en".into()])
+        );
+    }
+
+    #[test]
+    fn watch_reloads_changes() {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let path = dir.path().join("en.yml");
+        std::fs::write(&path, "greeting: hello").unwrap();
+
+        let pattern = dir.path().join("*.yml");
+        let (y10n, handle) =
+            Y10n::watch(&pattern.to_string_lossy()).expect("Failed to watch translations");
+        assert!(wait_for(&y10n, "hello"));
+
+        std::fs::write(&path, "greeting: howdy").unwrap();
+        assert!(wait_for(&y10n, "howdy"));
+
+        std::fs::write(&path, "greeting: [broken").unwrap();
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(wait_for(&y10n, "howdy"));
+
+        drop(handle);
+        std::fs::write(&path, "greeting: goodbye").unwrap();
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(wait_for(&y10n, "howdy"));
+    }
+}
@@ -0,0 +1,58 @@
+use std::process::Command;
+
+fn lint(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_y10n-lint"))
+        .args(args)
+        .output()
+        .expect("Failed to run y10n-lint");
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).expect("Invalid UTF-8 output"),
+    )
+}
+
+#[test]
+fn reports_problems() {
+    let (code, stdout) = lint(&["fixtures/lint/*.yml", "en"]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("fixtures/lint/fr.yml: Failed to deserialize translations"));
+    assert!(stdout.contains("de is missing the keys: farewell"));
+    assert!(stdout.contains("de: greeting is missing the placeholders: who"));
+}
+
+#[test]
+fn reports_json() {
+    let (code, stdout) = lint(&["--format", "json", "fixtures/lint/*.yml", "en"]);
+    assert_eq!(code, Some(1));
+
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(report["failures"][0]["path"], "fixtures/lint/fr.yml");
+    assert_eq!(
+        report["missing_keys"],
+        serde_json::json!([{"language": "de", "keys": ["farewell"]}])
+    );
+    assert_eq!(
+        report["placeholder_mismatches"],
+        serde_json::json!([{"language": "de", "key": "greeting", "missing": ["who"], "extra": []}])
+    );
+}
+
+#[test]
+fn passes_complete_translations() {
+    let (code, stdout) = lint(&["fixtures/lint/en.yml", "en"]);
+    assert_eq!(code, Some(0), "{}", stdout);
+    assert!(stdout.is_empty());
+}
+
+#[test]
+fn missing_base_language() {
+    let (code, stdout) = lint(&["fixtures/lint/en.yml", "fr"]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.contains("The reference language fr isn't loaded"));
+}
+
+#[test]
+fn invalid_arguments() {
+    assert_eq!(lint(&["l10n/*.yml"]).0, Some(2));
+    assert_eq!(lint(&["--format", "xml", "l10n/*.yml", "en"]).0, Some(2));
+}